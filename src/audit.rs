@@ -0,0 +1,328 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::completions::is_dotenv_file;
+use crate::models::{AuditArgs, ErrorKind};
+use crate::secrets::{self, SecretFilter};
+
+/// Variables that change how programs are loaded and can be used to inject code
+const DANGEROUS_VARIABLES: [&str; 3] = ["LD_PRELOAD", "LD_AUDIT", "DYLD_*"];
+
+/// How serious found issue is
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+/// Kind of check that found issue
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    Credential,
+    DangerousVariable,
+    RelativePath,
+    WritablePath,
+    DuplicatePath,
+    ReadableDotenv,
+}
+
+/// Issue found by audit
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub check: Check,
+    /// Variable or file issue is related to
+    pub subject: String,
+    pub message: String,
+}
+
+/// Audit environment or dotenv file given in args, most severe findings go first
+pub fn audit(args: &AuditArgs, secrets: &SecretFilter) -> Result<Vec<Finding>, ErrorKind> {
+    let (variables, files) = match &args.file {
+        Some(file) => {
+            let content =
+                fs::read_to_string(file).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+            let variables = dotenv_parser::parse_dotenv(&content)
+                .map_err(|err| ErrorKind::ParsingError(err.to_string()))?;
+            (variables.into_iter().collect(), vec![PathBuf::from(file)])
+        }
+        None => (
+            env::vars().collect::<Vec<_>>(),
+            fs::read_dir(".")
+                .map(|entries| {
+                    entries
+                        .filter_map(Result::ok)
+                        .map(|entry| entry.path())
+                        .filter(|path| {
+                            path.is_file()
+                                && path
+                                    .file_name()
+                                    .is_some_and(|name| is_dotenv_file(Path::new(name)))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+    };
+    let mut findings = audit_variables(&variables, secrets);
+    findings.extend(files.iter().filter_map(|file| audit_dotenv_file(file)));
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.subject.cmp(&b.subject))
+    });
+    Ok(findings)
+}
+
+/// Check variables for credentials, dangerous names and unsafe PATH entries
+pub fn audit_variables(variables: &[(String, String)], secrets: &SecretFilter) -> Vec<Finding> {
+    let mut findings = vec![];
+    for (name, value) in variables {
+        if secrets.is_secret(name, value) {
+            findings.push(Finding {
+                severity: Severity::High,
+                check: Check::Credential,
+                subject: name.clone(),
+                message: format!("{} looks like it contains credential", name),
+            });
+        }
+        if DANGEROUS_VARIABLES
+            .iter()
+            .any(|pattern| secrets::matches(pattern, name))
+        {
+            findings.push(Finding {
+                severity: Severity::Medium,
+                check: Check::DangerousVariable,
+                subject: name.clone(),
+                message: format!("{} changes how programs are loaded", name),
+            });
+        }
+        if name.eq_ignore_ascii_case("PATH") {
+            findings.extend(audit_path(name, value));
+        }
+    }
+    findings
+}
+
+/// Check PATH for relative, writable by others and duplicate entries
+fn audit_path(name: &str, value: &str) -> Vec<Finding> {
+    let mut findings = vec![];
+    let mut seen: Vec<PathBuf> = vec![];
+    for entry in env::split_paths(value) {
+        let shown = entry.display().to_string();
+        if entry.is_relative() {
+            findings.push(Finding {
+                severity: Severity::High,
+                check: Check::RelativePath,
+                subject: name.to_string(),
+                message: format!("{} contains relative entry {:?}", name, shown),
+            });
+        } else if is_writable_by_others(&entry) {
+            findings.push(Finding {
+                severity: Severity::High,
+                check: Check::WritablePath,
+                subject: name.to_string(),
+                message: format!("{} entry {} is writable by other users", name, shown),
+            });
+        }
+        if seen.contains(&entry) {
+            findings.push(Finding {
+                severity: Severity::Low,
+                check: Check::DuplicatePath,
+                subject: name.to_string(),
+                message: format!("{} contains {} more than once", name, shown),
+            });
+        } else {
+            seen.push(entry);
+        }
+    }
+    findings
+}
+
+/// Check whether dotenv file can be read by other users
+fn audit_dotenv_file(file: &Path) -> Option<Finding> {
+    is_readable_by_others(file).then(|| Finding {
+        severity: Severity::Medium,
+        check: Check::ReadableDotenv,
+        subject: file.display().to_string(),
+        message: format!("{} is readable by other users", file.display()),
+    })
+}
+
+#[cfg(unix)]
+fn is_writable_by_others(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o002 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_writable_by_others(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn is_readable_by_others(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o004 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_readable_by_others(_path: &Path) -> bool {
+    false
+}
+
+/// Write findings as table or JSON array
+pub fn write_report<W: Write>(findings: &[Finding], json: bool, mut buffer: W) {
+    if json {
+        let report = serde_json::to_string(findings).expect("Failed to serialize findings");
+        writeln!(buffer, "{}", report).expect("Failed to write to buffer");
+        return;
+    }
+    if findings.is_empty() {
+        writeln!(buffer, "No issues found").expect("Failed to write to buffer");
+        return;
+    }
+    for finding in findings {
+        let severity = format!("{:?}", finding.severity).to_uppercase();
+        writeln!(buffer, "{:<7} {}", severity, finding.message).expect("Failed to write to buffer");
+    }
+    writeln!(buffer, "Found {} issue(s)", findings.len()).expect("Failed to write to buffer");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(variables: &[(&str, &str)]) -> Vec<(String, String)> {
+        variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_credentials_and_dangerous_variables() {
+        let findings = audit_variables(
+            &variables(&[
+                ("GITHUB_TOKEN", "abc"),
+                ("DB_PASSWORD", "123456"),
+                ("MAX_TOKENS", "4096"),
+                ("LD_PRELOAD", "/tmp/hook.so"),
+                ("DYLD_INSERT_LIBRARIES", "/tmp/hook.dylib"),
+                ("HOME", "/root"),
+            ]),
+            &SecretFilter::default(),
+        );
+        let checks: Vec<(Check, &str)> = findings
+            .iter()
+            .map(|finding| (finding.check, finding.subject.as_str()))
+            .collect();
+        assert_eq!(
+            checks,
+            vec![
+                (Check::Credential, "GITHUB_TOKEN"),
+                (Check::Credential, "DB_PASSWORD"),
+                (Check::DangerousVariable, "LD_PRELOAD"),
+                (Check::DangerousVariable, "DYLD_INSERT_LIBRARIES"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audit_path_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let absolute = dir.path().display().to_string();
+        let path = env::join_paths([absolute.as_str(), "bin", absolute.as_str()])
+            .unwrap()
+            .into_string()
+            .unwrap();
+        let findings = audit_path("PATH", &path);
+        let checks: Vec<(Severity, Check)> = findings
+            .iter()
+            .map(|finding| (finding.severity, finding.check))
+            .collect();
+        assert_eq!(
+            checks,
+            vec![
+                (Severity::High, Check::RelativePath),
+                (Severity::Low, Check::DuplicatePath),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_audit_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o777)).unwrap();
+        let findings = audit_path("PATH", &dir.path().display().to_string());
+        assert_eq!(findings[0].check, Check::WritablePath);
+
+        let file = dir.path().join(".env");
+        fs::write(&file, "A=1").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(
+            audit_dotenv_file(&file).map(|finding| finding.check),
+            Some(Check::ReadableDotenv)
+        );
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(audit_dotenv_file(&file).is_none());
+    }
+
+    #[test]
+    fn test_audit_dotenv_file_sorted_by_severity() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".env");
+        fs::write(&file, "LD_PRELOAD=hook.so\nAPI_SECRET=abc\n").unwrap();
+        let args = AuditArgs {
+            file: Some(file.display().to_string()),
+            json: false,
+        };
+        let findings = audit(&args, &SecretFilter::default()).unwrap();
+        assert_eq!(findings[0].check, Check::Credential);
+        assert!(
+            findings
+                .iter()
+                .any(|finding| finding.check == Check::DangerousVariable)
+        );
+        assert!(
+            findings
+                .windows(2)
+                .all(|pair| pair[0].severity >= pair[1].severity)
+        );
+    }
+
+    #[test]
+    fn test_write_report() {
+        let findings = vec![Finding {
+            severity: Severity::High,
+            check: Check::Credential,
+            subject: "API_TOKEN".to_string(),
+            message: "API_TOKEN looks like it contains credential".to_string(),
+        }];
+        let mut buffer = vec![];
+        write_report(&findings, false, &mut buffer);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "HIGH    API_TOKEN looks like it contains credential\nFound 1 issue(s)\n"
+        );
+        let mut buffer = vec![];
+        write_report(&findings, true, &mut buffer);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "[{\"severity\":\"high\",\"check\":\"credential\",\"subject\":\"API_TOKEN\",\"message\":\"API_TOKEN looks like it contains credential\"}]\n"
+        );
+        let mut buffer = vec![];
+        write_report(&[], false, &mut buffer);
+        assert_eq!(String::from_utf8(buffer).unwrap(), "No issues found\n");
+    }
+}
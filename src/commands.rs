@@ -1,16 +1,34 @@
 use log::error;
 use log::warn;
 use rayon::prelude::*;
-use std::io::Write;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
 use std::process::ExitCode;
 use std::process::ExitStatus;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{env, fs};
 
+use crate::audit;
+use crate::completions;
 use crate::config;
+use crate::docs;
+use crate::format::{Entry, Layout, Template};
+use crate::global;
+use crate::history;
+use crate::hook;
 use crate::interactive::InteractiveApp;
 use crate::models::*;
+use crate::render;
+use crate::secrets::SecretFilter;
 use crate::utils::*;
-use crate::variables;
+use crate::variables::{self, PlannedChange};
+
+/// Variable that marks shells started by shell command
+const SHELL_MARKER: &str = "ENVFETCH_SHELL";
+
+/// Prompt prefix for shells started by shell command
+const SHELL_PROMPT: &str = "(envfetch) ";
 
 /// Run tool's command
 pub fn run_command<W: Write>(
@@ -18,6 +36,21 @@ pub fn run_command<W: Write>(
     config: Option<Config>,
     mut buffer: W,
 ) -> ExitCode {
+    // Use validation profile from config if command doesn't set one
+    let resolved = config
+        .as_ref()
+        .and_then(|config| config.validation)
+        .and_then(|profile| with_validation(command, profile));
+    let command = resolved.as_ref().unwrap_or(command);
+    // Dry run prints suggestions after the plan instead
+    let dry_run = matches!(
+        command,
+        Commands::Add(AddArgs { dry_run: true, .. })
+            | Commands::Delete(DeleteArgs { dry_run: true, .. })
+    );
+    if !dry_run {
+        suggest_existing_names(command, config.as_ref());
+    }
     match command {
         Commands::InitConfig => {
             if let Err(error) = config::init_config(config::get_config_file_path(), buffer) {
@@ -26,41 +59,142 @@ pub fn run_command<W: Write>(
             }
         }
         Commands::Get(opt) => {
-            if let Err(error) = get(opt, &mut buffer) {
+            let raw_by_default = config
+                .as_ref()
+                .and_then(|config| config.get_raw)
+                .unwrap_or_default();
+            let opt = if raw_by_default && !opt.quoted && opt.format.is_none() && !opt.json {
+                &GetArgs {
+                    raw: true,
+                    ..opt.clone()
+                }
+            } else {
+                opt
+            };
+            if let Err(error) = get(opt, config.as_ref(), &mut buffer) {
                 error!("{}", error);
-                if let ErrorKind::CannotFindVariable(key, no_similar_names) = error {
-                    if !no_similar_names {
-                        let similar_names = find_similar_string(
-                            key.clone(),
-                            env::vars().map(|(key, _)| key).collect(),
-                            0.6,
-                        );
-                        if !similar_names.is_empty() {
-                            writeln!(&mut buffer, "Did you mean:")
-                                .expect("Failed to write to buffer");
-                            for name in similar_names {
-                                writeln!(&mut buffer, "  {}", &name)
-                                    .expect("Failed to write to buffer");
-                            }
-                        }
-                    }
+                if let ErrorKind::CannotFindVariable(key, no_similar_names) = error
+                    && !no_similar_names
+                {
+                    print_similar_names(&key, config.as_ref(), &mut buffer);
                 }
                 return ExitCode::FAILURE;
             }
         }
         Commands::Print(opt) => {
-            let opt = if opt.format.is_some() {
-                opt
-            } else if let Some(config) = config {
-                &PrintArgs {
-                    format: config.print_format,
+            if let Err(error) = print_env(opt, config.as_ref(), buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Global(opt) => {
+            if let Err(error) = global(opt, &config::get_history_file_path(), &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::History => {
+            if let Err(error) = print_history(&config::get_history_file_path(), &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Undo(opt) => {
+            if let Err(error) = undo(opt, &config::get_history_file_path(), &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Shell(opt) => match shell(opt, &mut buffer) {
+            Ok(code) => return ExitCode::from(code.code().unwrap_or_default() as u8),
+            Err(error) => {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        },
+        Commands::Completions(opt) => completions::generate_completions(opt.shell, buffer),
+        Commands::Render(opt) => {
+            if let Err(error) = render(opt, &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Audit(opt) => {
+            let secrets = SecretFilter::from_config(config.as_ref());
+            match audit::audit(opt, &secrets) {
+                Ok(findings) => {
+                    audit::write_report(&findings, opt.json, &mut buffer);
+                    if !findings.is_empty() {
+                        return ExitCode::FAILURE;
+                    }
                 }
-            } else {
-                opt
+                Err(error) => {
+                    error!("{}", error);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        Commands::Docs(opt) => {
+            if let Err(error) = docs::generate_docs(opt, &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Complete(opt) => {
+            for candidate in completions::complete(&opt.words) {
+                writeln!(buffer, "{}", candidate).expect("Failed to write to buffer");
+            }
+        }
+        Commands::Hook(opt) => {
+            let executable = env::current_exe()
+                .map(|path| path.display().to_string())
+                .unwrap_or("envfetch".to_owned());
+            write!(buffer, "{}", hook::render_hook(opt.shell, &executable))
+                .expect("Failed to write to buffer");
+        }
+        Commands::HookEnv(opt) => {
+            let Ok(dir) = env::current_dir() else {
+                return ExitCode::FAILURE;
             };
-            print_env(opt, buffer)
+            let state = env::var(hook::STATE_VARIABLE).ok();
+            let statements =
+                hook::hook_env(opt.shell, &dir, state.as_deref(), |key| env::var(key).ok());
+            write!(buffer, "{}", statements).expect("Failed to write to buffer");
+        }
+        Commands::Allow(opt) => {
+            if let Err(error) = allow(opt, &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Load(opt) if opt.dry_run => {
+            if let Err(error) = print_plan(plan_load(opt), opt.global, &opt.process, &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Set(opt) if opt.dry_run => {
+            if let Err(error) = print_plan(plan_set(opt), opt.global, &opt.process, &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Commands::Add(opt) if opt.dry_run => {
+            if let Err(error) = print_plan(plan_add(opt), opt.global, &opt.process, &mut buffer) {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+            suggest_existing_names(command, config.as_ref());
         }
-        Commands::Load(opt) => match load(opt) {
+        Commands::Delete(opt) if opt.dry_run => {
+            if let Err(error) = print_plan(plan_delete(opt), opt.global, &opt.process, &mut buffer)
+            {
+                error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+            suggest_existing_names(command, config.as_ref());
+        }
+        Commands::Load(opt) => match load(opt, &config::get_history_file_path()) {
             Ok(code) => {
                 if let Some(exit_code) = code {
                     return ExitCode::from(exit_code.code().unwrap_or_default() as u8);
@@ -71,7 +205,7 @@ pub fn run_command<W: Write>(
                 return ExitCode::FAILURE;
             }
         },
-        Commands::Set(opt) => match set(opt) {
+        Commands::Set(opt) => match set(opt, &config::get_history_file_path()) {
             Ok(code) => {
                 if let Some(exit_code) = code {
                     return ExitCode::from(exit_code.code().unwrap_or_default() as u8);
@@ -82,7 +216,7 @@ pub fn run_command<W: Write>(
                 return ExitCode::FAILURE;
             }
         },
-        Commands::Add(opt) => match add(opt) {
+        Commands::Add(opt) => match add(opt, &config::get_history_file_path()) {
             Ok(code) => {
                 if let Some(exit_code) = code {
                     return ExitCode::from(exit_code.code().unwrap_or_default() as u8);
@@ -93,7 +227,7 @@ pub fn run_command<W: Write>(
                 return ExitCode::FAILURE;
             }
         },
-        Commands::Delete(opt) => match delete(opt) {
+        Commands::Delete(opt) => match delete(opt, &config::get_history_file_path()) {
             Ok(code) => {
                 if let Some(exit_code) = code {
                     return ExitCode::from(exit_code.code().unwrap_or_default() as u8);
@@ -104,144 +238,575 @@ pub fn run_command<W: Write>(
                 return ExitCode::FAILURE;
             }
         },
-        Commands::Interactive => {
+        Commands::Interactive(opt) => {
+            let secrets = SecretFilter::from_config(config.as_ref());
+            let mut app = match InteractiveApp::new(secrets, opt) {
+                Ok(app) => app,
+                Err(error) => {
+                    error!("{}", error);
+                    return ExitCode::FAILURE;
+                }
+            };
             #[cfg(not(test))]
-            let mut terminal = ratatui::init();
+            let mut terminal = match crate::interactive::init_terminal(opt.export.is_some()) {
+                Ok(terminal) => terminal,
+                Err(error) => {
+                    error!("{}", error);
+                    return ExitCode::FAILURE;
+                }
+            };
             #[cfg(test)]
             let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 100))
                 .expect("Failed to create TestBackend terminal");
-            let result = InteractiveApp::new().run(&mut terminal);
-            ratatui::restore();
+            let result = app.run(&mut terminal);
+            #[cfg(not(test))]
+            let _ = crate::interactive::restore_terminal(opt.export.is_some());
             if let Err(error) = result {
                 error!("{}", error);
                 return ExitCode::FAILURE;
             }
+            if let Some(shell) = opt.export {
+                let statements = hook::export_statements(shell, &app.exported_changes());
+                write!(buffer, "{}", statements).expect("Failed to write to buffer");
+            }
         }
     }
     ExitCode::SUCCESS
 }
 
+/// Get copy of command with validation profile set, if command validates names and doesn't set it
+fn with_validation(command: &Commands, profile: ValidationProfile) -> Option<Commands> {
+    let validation = Some(profile);
+    match command {
+        Commands::Set(opt) if opt.validation.is_none() => Some(Commands::Set(SetArgs {
+            validation,
+            ..opt.clone()
+        })),
+        Commands::Add(opt) if opt.validation.is_none() => Some(Commands::Add(AddArgs {
+            validation,
+            ..opt.clone()
+        })),
+        Commands::Delete(opt) if opt.validation.is_none() => Some(Commands::Delete(DeleteArgs {
+            validation,
+            ..opt.clone()
+        })),
+        Commands::Load(opt) if opt.validation.is_none() => Some(Commands::Load(LoadArgs {
+            validation,
+            ..opt.clone()
+        })),
+        Commands::Interactive(opt) if opt.validation.is_none() => {
+            Some(Commands::Interactive(InteractiveArgs {
+                validation,
+                ..opt.clone()
+            }))
+        }
+        Commands::Shell(opt) if opt.validation.is_none() => Some(Commands::Shell(ShellArgs {
+            validation,
+            ..opt.clone()
+        })),
+        _ => None,
+    }
+}
+
+/// Point at existing names when adding to or deleting variable that doesn't exist
+///
+/// Suggestions are printed to stderr, so stdout is left for output of command.
+fn suggest_existing_names(command: &Commands, config: Option<&Config>) {
+    if let Commands::Add(AddArgs { key, .. }) | Commands::Delete(DeleteArgs { key, .. }) = command
+        && env::var(key).is_err()
+    {
+        print_similar_names(key, config, io::stderr());
+    }
+}
+
+/// Print names of environment variables similar to given one, if there are any
+pub fn print_similar_names<W: Write>(key: &str, config: Option<&Config>, mut buffer: W) {
+    let threshold = config
+        .and_then(|config| config.suggestion_threshold)
+        .unwrap_or(DEFAULT_SUGGESTION_THRESHOLD);
+    let count = config
+        .and_then(|config| config.suggestion_count)
+        .unwrap_or(DEFAULT_SUGGESTION_COUNT);
+    let similar_names = find_similar_string(
+        key.to_string(),
+        env::vars().map(|(key, _)| key).collect(),
+        threshold,
+        count,
+    );
+    if !similar_names.is_empty() {
+        writeln!(&mut buffer, "Did you mean:").expect("Failed to write to buffer");
+        for name in similar_names {
+            writeln!(&mut buffer, "  {}", &name).expect("Failed to write to buffer");
+        }
+    }
+}
+
 /// Print all environment variables
-pub fn print_env<W: Write>(opt: &PrintArgs, buffer: W) {
-    let format = &opt
-        .format
-        .clone()
-        .unwrap_or("{name} = \"{value}\"".to_owned());
-    // Print all environment variables
-    variables::print_env(format, buffer);
+pub fn print_env<W: Write>(
+    opt: &PrintArgs,
+    config: Option<&Config>,
+    buffer: W,
+) -> Result<(), ErrorKind> {
+    let layout = print_layout(opt, config.and_then(|config| config.print_format.clone()));
+    let secrets = (!opt.reveal).then(|| SecretFilter::from_config(config));
+    variables::print_env(&layout, secrets.as_ref(), buffer)
 }
 
-/// Load variables from dotenv-style file
-pub fn load(args: &LoadArgs) -> Result<Option<ExitStatus>, ErrorKind> {
-    // Try to read file
-    match fs::read_to_string(&args.file) {
-        Ok(content) => {
-            // Try to parse file
-            match dotenv_parser::parse_dotenv(&content) {
-                Ok(variables) => {
-                    variables.into_par_iter().try_for_each(
-                        |(key, value)| -> Result<(), ErrorKind> {
-                            variables::set_variable(&key, &value, args.global)
-                        },
-                    )?;
-                    if !args.process.is_empty() {
-                        let process = args.process.join(" ");
-                        return run(process).map(Some);
-                    }
-                }
-                Err(err) => {
-                    return Err(ErrorKind::ParsingError(err.to_string()));
-                }
+/// Get layout of print command, options from command line take precedence over config
+fn print_layout(opt: &PrintArgs, config: Option<PrintFormat>) -> Layout {
+    let (entry, header, footer, separator) = match config {
+        Some(PrintFormat::Entry(entry)) => (Some(entry), None, None, None),
+        Some(PrintFormat::Layout {
+            entry,
+            header,
+            footer,
+            separator,
+        }) => (entry, header, footer, separator),
+        None => (None, None, None, None),
+    };
+    let default = Layout::new(
+        &opt.format
+            .clone()
+            .or(entry)
+            .unwrap_or("{name} = \"{value}\"".to_owned()),
+    );
+    Layout {
+        header: opt.header.clone().or(header),
+        footer: opt.footer.clone().or(footer),
+        separator: opt
+            .separator
+            .clone()
+            .or(separator)
+            .unwrap_or(default.separator),
+        ..default
+    }
+}
+
+/// Inspect variables persisted by global operations
+pub fn global<W: Write>(args: &GlobalArgs, journal: &Path, mut buffer: W) -> Result<(), ErrorKind> {
+    let store = global::get_store()?;
+    match &args.command {
+        GlobalCommands::List => {
+            for variable in global::read_variables(&store)? {
+                writeln!(buffer, "{}", variable).expect("Failed to write to buffer");
             }
         }
-        Err(err) => {
-            return Err(ErrorKind::FileError(err.to_string()));
+        GlobalCommands::Get(opt) => {
+            let variable = global::get_variable(&store, &opt.key)?;
+            writeln!(buffer, "{}", variable).expect("Failed to write to buffer");
+        }
+        GlobalCommands::Remove(opt) => {
+            let variable = global::get_variable(&store, &opt.key)?;
+            global::remove_variable(&store, &opt.key)?;
+            history::record_change(journal, &opt.key, Some(variable.value.clone()), None)?;
+            writeln!(buffer, "Removed {} from {}", variable.name, store)
+                .expect("Failed to write to buffer");
         }
     }
-    Ok(None)
+    Ok(())
 }
 
-/// Get value of variable
-pub fn get<W: Write>(args: &GetArgs, mut buffer: W) -> Result<(), ErrorKind> {
-    // Check if variable with specified name exists
-    match env::var(&args.key) {
-        Ok(value) => writeln!(buffer, "{:?}", &value).expect("Failed to write to buffer"),
-        // If variable not found
-        _ => {
-            return Err(ErrorKind::CannotFindVariable(
-                args.key.clone(),
-                args.no_similar_names,
-            ));
-        }
+/// Print journal of global modifications at given path, newest first
+pub fn print_history<W: Write>(journal: &Path, mut buffer: W) -> Result<(), ErrorKind> {
+    let changes = history::get_changes(journal)?;
+    if changes.is_empty() {
+        warn!("history is empty");
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    for (index, change) in changes.iter().enumerate().rev() {
+        writeln!(
+            buffer,
+            "{:>4}  {:>8}  {}: {} -> {}",
+            index + 1,
+            history::format_age(change.timestamp, now),
+            change.key,
+            format_history_value(&change.previous),
+            format_history_value(&change.value),
+        )
+        .expect("Failed to write to buffer");
     }
     Ok(())
 }
 
-/// Set value to environment variable
-pub fn set(args: &SetArgs) -> Result<Option<ExitStatus>, ErrorKind> {
-    validate_var_name(&args.key).map_err(ErrorKind::NameValidationError)?;
+/// Format value from journal, missing values are shown as (unset)
+fn format_history_value(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("{:?}", value),
+        None => "(unset)".to_string(),
+    }
+}
 
-    variables::set_variable(&args.key, &args.value, args.global)?;
-    if !args.process.is_empty() {
-        let process = args.process.join(" ");
-        return run(process).map(Some);
+/// Revert latest global modifications recorded in journal at given path
+pub fn undo<W: Write>(args: &UndoArgs, journal: &Path, mut buffer: W) -> Result<(), ErrorKind> {
+    for _ in 0..args.count {
+        let Some(change) = history::get_changes(journal)?.pop() else {
+            warn!("nothing to undo");
+            break;
+        };
+        variables::revert_change(&change)?;
+        history::pop_change(journal)?;
+        writeln!(
+            buffer,
+            "Reverted {} to {}",
+            change.key,
+            format_history_value(&change.previous)
+        )
+        .expect("Failed to write to buffer");
     }
-    Ok(None)
+    Ok(())
 }
 
-/// Add value to environment variable
-pub fn add(args: &AddArgs) -> Result<Option<ExitStatus>, ErrorKind> {
-    validate_var_name(&args.key).map_err(ErrorKind::NameValidationError)?;
+/// Start user's shell with modified environment
+pub fn shell<W: Write>(args: &ShellArgs, mut buffer: W) -> Result<ExitStatus, ErrorKind> {
+    if !args.nested && env::var(SHELL_MARKER).is_ok() {
+        return Err(ErrorKind::NestedShellError);
+    }
+    let variables = shell_variables(args)?;
+    let shell = get_user_shell();
+    writeln!(
+        buffer,
+        "Starting {} with {} modified variables, type `exit` to leave",
+        shell,
+        variables.len()
+    )
+    .expect("Failed to write to buffer");
+
+    let mut command = Command::new(&shell);
+    command
+        .envs(variables)
+        .env(SHELL_MARKER, "1")
+        .env("ENVFETCH_PROMPT", SHELL_PROMPT);
+    // Shells usually reset prompt in their rc files, but keep hint if it's exported
+    if let Ok(prompt) = env::var("PS1") {
+        command.env("PS1", format!("{}{}", SHELL_PROMPT, prompt));
+    }
+    command.status().map_err(|err| {
+        error!("can't start {}: {}", shell, err);
+        ErrorKind::StartingProcessError
+    })
+}
 
-    let current_value = if let Ok(value) = env::var(&args.key) {
-        value
-    } else {
-        "".to_string()
-    };
+/// Get validated variables for shell command from dotenv file and --set arguments
+pub fn shell_variables(args: &ShellArgs) -> Result<Vec<(String, String)>, ErrorKind> {
+    let mut variables = vec![];
+    if let Some(file) = &args.file {
+        let content =
+            fs::read_to_string(file).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+        variables.extend(
+            dotenv_parser::parse_dotenv(&content)
+                .map_err(|err| ErrorKind::ParsingError(err.to_string()))?,
+        );
+    }
+    for variable in &args.set {
+        let (key, value) = variable.split_once('=').ok_or_else(|| {
+            ErrorKind::ParsingError(format!("expected KEY=VALUE, got {}", variable))
+        })?;
+        variables.push((key.to_string(), value.to_string()));
+    }
+    for (key, value) in &variables {
+        validate_var_name(key, args.validation.unwrap_or_default())
+            .map_err(ErrorKind::NameValidationError)?;
+        validate_var_value(value).map_err(ErrorKind::ValueValidationError)?;
+    }
+    Ok(variables)
+}
 
-    variables::set_variable(
-        &args.key,
-        &format!("{}{}", current_value, args.value),
-        args.global,
+/// Render template with variables from environment and optional dotenv file
+pub fn render<W: Write>(args: &RenderArgs, mut buffer: W) -> Result<(), ErrorKind> {
+    let template =
+        fs::read_to_string(&args.template).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+    let overrides = match &args.file {
+        Some(file) => {
+            let content =
+                fs::read_to_string(file).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+            dotenv_parser::parse_dotenv(&content)
+                .map_err(|err| ErrorKind::ParsingError(err.to_string()))?
+        }
+        None => Default::default(),
+    };
+    let output = render::render_template(
+        &template,
+        |key| overrides.get(key).cloned().or_else(|| env::var(key).ok()),
+        args.escape,
     )?;
-    if !args.process.is_empty() {
-        let process = args.process.join(" ");
-        return run(process).map(Some);
+    write!(buffer, "{}", output).expect("Failed to write to buffer");
+    Ok(())
+}
+
+/// Allow shell hook to load dotenv file
+pub fn allow<W: Write>(args: &AllowArgs, mut buffer: W) -> Result<(), ErrorKind> {
+    let file = match &args.file {
+        Some(file) => file.into(),
+        None => {
+            let dir = env::current_dir().map_err(|err| ErrorKind::FileError(err.to_string()))?;
+            hook::find_dotenv(&dir)
+                .ok_or_else(|| ErrorKind::FileError("can't find .env file".to_string()))?
+        }
+    };
+    let file = hook::allow_file(&file)?;
+    writeln!(buffer, "Allowed {}", file.display()).expect("Failed to write to buffer");
+    Ok(())
+}
+
+/// Load variables from dotenv-style file, recording global changes in journal at given path
+pub fn load(args: &LoadArgs, journal: &Path) -> Result<Option<ExitStatus>, ErrorKind> {
+    let changes = plan_load(args)?;
+    changes
+        .par_iter()
+        .try_for_each(|change| variables::apply_change(change, args.global, journal))?;
+    run_process(&args.process)
+}
+
+/// Get changes that load command would make
+pub fn plan_load(args: &LoadArgs) -> Result<Vec<PlannedChange>, ErrorKind> {
+    // Try to read file
+    let content =
+        fs::read_to_string(&args.file).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+    // Try to parse file
+    let variables = dotenv_parser::parse_dotenv(&content)
+        .map_err(|err| ErrorKind::ParsingError(err.to_string()))?;
+    variables
+        .into_iter()
+        .map(|(key, value)| {
+            validate_var_name(&key, args.validation.unwrap_or_default())
+                .map_err(ErrorKind::NameValidationError)?;
+            validate_var_value(&value).map_err(ErrorKind::ValueValidationError)?;
+            Ok(PlannedChange {
+                old: env::var(&key).ok(),
+                key,
+                new: Some(value),
+            })
+        })
+        .collect()
+}
+
+/// Get value of variable
+pub fn get<W: Write>(
+    args: &GetArgs,
+    config: Option<&Config>,
+    mut buffer: W,
+) -> Result<(), ErrorKind> {
+    // Check that every variable exists before printing anything
+    let mut variables = vec![];
+    for key in &args.keys {
+        let key = &resolve_key(key, args, config)?;
+        match env::var(key).ok().or_else(|| args.default.clone()) {
+            Some(value) => variables.push((key.clone(), value)),
+            // If variable not found
+            None => {
+                return Err(ErrorKind::CannotFindVariable(
+                    key.clone(),
+                    args.no_similar_names,
+                ));
+            }
+        }
+    }
+
+    if args.json {
+        // Map keeps requested order and lists repeated names once
+        let object: serde_json::Map<String, serde_json::Value> = variables
+            .into_iter()
+            .map(|(key, value)| (key, serde_json::Value::String(value)))
+            .collect();
+        let json = serde_json::to_string(&object).expect("Failed to serialize variables");
+        writeln!(buffer, "{}", json).expect("Failed to write to buffer");
+        return Ok(());
+    }
+    let template = args.format.as_deref().map(Template::parse).transpose()?;
+    let len = variables.len();
+    for (index, (key, value)) in variables.into_iter().enumerate() {
+        if let Some(template) = &template {
+            let entry = template.render(&Entry {
+                name: &key,
+                value: &value,
+                index: index + 1,
+                len,
+                secret: false,
+            });
+            writeln!(buffer, "{}", entry).expect("Failed to write to buffer");
+        } else if args.raw {
+            writeln!(buffer, "{}", value).expect("Failed to write to buffer");
+        } else {
+            writeln!(buffer, "{:?}", value).expect("Failed to write to buffer");
+        }
     }
-    Ok(None)
+    Ok(())
 }
 
-/// Delete environment variable
-pub fn delete(args: &DeleteArgs) -> Result<Option<ExitStatus>, ErrorKind> {
-    validate_var_name(&args.key).map_err(ErrorKind::NameValidationError)?;
+/// Find actual name of variable when case-insensitive or fuzzy lookup is requested
+fn resolve_key(key: &str, args: &GetArgs, config: Option<&Config>) -> Result<String, ErrorKind> {
+    if env::var(key).is_ok() || !(args.ignore_case || args.fuzzy) {
+        return Ok(key.to_string());
+    }
+    let names: Vec<String> = env::vars().map(|(name, _)| name).collect();
+    let candidates: Vec<String> = if args.fuzzy {
+        let threshold = config
+            .and_then(|config| config.suggestion_threshold)
+            .unwrap_or(DEFAULT_SUGGESTION_THRESHOLD);
+        let ranked = rank_similar_strings(key, &names, threshold);
+        // Only names sharing the best score are equally good matches
+        let best = ranked.first().map(|(score, _)| *score);
+        ranked
+            .into_iter()
+            .filter(|(score, _)| Some(*score) == best)
+            .map(|(_, name)| name)
+            .collect()
+    } else {
+        let mut candidates: Vec<String> = names
+            .into_iter()
+            .filter(|name| name.to_lowercase() == key.to_lowercase())
+            .collect();
+        candidates.sort();
+        candidates
+    };
+    match candidates.as_slice() {
+        [] => Ok(key.to_string()),
+        [name] => {
+            warn!("using {} for {}", name, key);
+            Ok(name.clone())
+        }
+        _ => Err(ErrorKind::AmbiguousVariable(key.to_string(), candidates)),
+    }
+}
+
+/// Set value to environment variable, recording global change in journal at given path
+pub fn set(args: &SetArgs, journal: &Path) -> Result<Option<ExitStatus>, ErrorKind> {
+    let changes = plan_set(args)?;
+    apply_changes(&changes, args.global, journal)?;
+    run_process(&args.process)
+}
+
+/// Get changes that set command would make
+pub fn plan_set(args: &SetArgs) -> Result<Vec<PlannedChange>, ErrorKind> {
+    validate_var_name(&args.key, args.validation.unwrap_or_default())
+        .map_err(ErrorKind::NameValidationError)?;
+    validate_var_value(&args.value).map_err(ErrorKind::ValueValidationError)?;
+
+    Ok(vec![PlannedChange {
+        key: args.key.clone(),
+        old: env::var(&args.key).ok(),
+        new: Some(args.value.clone()),
+    }])
+}
+
+/// Add value to environment variable, recording global change in journal at given path
+pub fn add(args: &AddArgs, journal: &Path) -> Result<Option<ExitStatus>, ErrorKind> {
+    let changes = plan_add(args)?;
+    apply_changes(&changes, args.global, journal)?;
+    run_process(&args.process)
+}
+
+/// Get changes that add command would make
+pub fn plan_add(args: &AddArgs) -> Result<Vec<PlannedChange>, ErrorKind> {
+    validate_var_name(&args.key, args.validation.unwrap_or_default())
+        .map_err(ErrorKind::NameValidationError)?;
+    validate_var_value(&args.value).map_err(ErrorKind::ValueValidationError)?;
+
+    let current_value = env::var(&args.key).ok();
+    Ok(vec![PlannedChange {
+        key: args.key.clone(),
+        new: Some(format!(
+            "{}{}",
+            current_value.as_deref().unwrap_or_default(),
+            args.value
+        )),
+        old: current_value,
+    }])
+}
+
+/// Delete environment variable, recording global change in journal at given path
+pub fn delete(args: &DeleteArgs, journal: &Path) -> Result<Option<ExitStatus>, ErrorKind> {
+    let changes = plan_delete(args)?;
+    apply_changes(&changes, args.global, journal)?;
+    run_process(&args.process)
+}
+
+/// Get changes that delete command would make
+pub fn plan_delete(args: &DeleteArgs) -> Result<Vec<PlannedChange>, ErrorKind> {
+    validate_var_name(&args.key, args.validation.unwrap_or_default())
+        .map_err(ErrorKind::NameValidationError)?;
 
     // Check if variable exists
-    match env::var(&args.key) {
-        Ok(_) => {
-            variables::delete_variable(args.key.clone(), args.global)?;
-        }
-        _ => {
-            warn!("{}", "variable doesn't exists");
+    let Ok(current_value) = env::var(&args.key) else {
+        warn!("{}", "variable doesn't exists");
+        return Ok(vec![]);
+    };
+    Ok(vec![PlannedChange {
+        key: args.key.clone(),
+        old: Some(current_value),
+        new: None,
+    }])
+}
+
+/// Apply planned changes one by one
+fn apply_changes(changes: &[PlannedChange], global: bool, journal: &Path) -> Result<(), ErrorKind> {
+    changes
+        .iter()
+        .try_for_each(|change| variables::apply_change(change, global, journal))
+}
+
+/// Run given process if it isn't empty
+fn run_process(process: &[String]) -> Result<Option<ExitStatus>, ErrorKind> {
+    if process.is_empty() {
+        return Ok(None);
+    }
+    run(process.join(" ")).map(Some)
+}
+
+/// Print planned changes without applying them or starting process
+pub fn print_plan<W: Write>(
+    changes: Result<Vec<PlannedChange>, ErrorKind>,
+    global: bool,
+    process: &[String],
+    mut buffer: W,
+) -> Result<(), ErrorKind> {
+    let changes = changes?;
+    writeln!(buffer, "Dry run, nothing will be changed").expect("Failed to write to buffer");
+    if global {
+        writeln!(buffer, "Would modify {}", global::get_store()?)
+            .expect("Failed to write to buffer");
+    }
+    if changes.is_empty() {
+        writeln!(buffer, "No variables would be changed").expect("Failed to write to buffer");
+    }
+    for change in changes {
+        writeln!(buffer, "  {}", change).expect("Failed to write to buffer");
+        // Changes are based on current session, persisted value may differ
+        if global {
+            let persisted = variables::current_value(&change.key, true)?;
+            if persisted != change.old {
+                match persisted {
+                    Some(value) => writeln!(buffer, "    currently persisted as {:?}", value),
+                    None => writeln!(buffer, "    not persisted yet"),
+                }
+                .expect("Failed to write to buffer");
+            }
         }
     }
-    if !args.process.is_empty() {
-        let process = args.process.join(" ");
-        return run(process).map(Some);
+    if !process.is_empty() {
+        writeln!(buffer, "Would run: {}", process.join(" ")).expect("Failed to write to buffer");
     }
-    Ok(None)
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use tempfile::NamedTempFile;
-
     use super::*;
     use std::io::Write;
+    use tempfile::NamedTempFile;
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
     }
 
+    /// Get temporary journal, so tests never record changes in user's journal
+    fn temp_journal() -> tempfile::TempPath {
+        NamedTempFile::new().unwrap().into_temp_path()
+    }
+
     #[test]
     fn test_run_command_get_success() {
         init();
@@ -249,8 +814,15 @@ mod tests {
         let mut buffer = vec![];
         run_command(
             &Commands::Get(GetArgs {
-                key: "TEST_RUN_VAR".to_string(),
+                keys: vec!["TEST_RUN_VAR".to_string()],
                 no_similar_names: false,
+                raw: false,
+                quoted: false,
+                default: None,
+                format: None,
+                json: false,
+                ignore_case: false,
+                fuzzy: false,
             }),
             None,
             &mut buffer,
@@ -270,8 +842,15 @@ mod tests {
         assert_eq!(
             run_command(
                 &Commands::Get(GetArgs {
-                    key: "TEST_RUN_VAR_awzsenfkaqyG".to_string(),
+                    keys: vec!["TEST_RUN_VAR_awzsenfkaqyG".to_string()],
                     no_similar_names: false,
+                    raw: false,
+                    quoted: false,
+                    default: None,
+                    format: None,
+                    json: false,
+                    ignore_case: false,
+                    fuzzy: false,
                 }),
                 None,
                 &mut buffer
@@ -289,6 +868,8 @@ mod tests {
                 key: "TEST_SET_RUN".to_string(),
                 value: "test_value".to_string(),
                 global: false,
+                dry_run: false,
+                validation: None,
                 process: vec![],
             }),
             None,
@@ -310,6 +891,8 @@ mod tests {
                 key: "TEST_ADD_RUN".to_string(),
                 value: "value".to_string(),
                 global: false,
+                dry_run: false,
+                validation: None,
                 process: vec![],
             }),
             None,
@@ -325,7 +908,13 @@ mod tests {
         unsafe { env::set_var("TEST_PRINT_RUN", "test_value") };
         let mut buffer = vec![];
         run_command(
-            &Commands::Print(PrintArgs { format: None }),
+            &Commands::Print(PrintArgs {
+                format: None,
+                header: None,
+                footer: None,
+                separator: None,
+                reveal: false,
+            }),
             None,
             &mut buffer,
         );
@@ -344,9 +933,16 @@ mod tests {
         unsafe { env::set_var("TEST_PRINT_RUN_CONFIG", "test_value") };
         let mut buffer = vec![];
         run_command(
-            &Commands::Print(PrintArgs { format: None }),
+            &Commands::Print(PrintArgs {
+                format: None,
+                header: None,
+                footer: None,
+                separator: None,
+                reveal: false,
+            }),
             Some(Config {
-                print_format: Some("{name} = {value}".to_owned()),
+                print_format: Some(PrintFormat::Entry("{name} = {value}".to_owned())),
+                ..Default::default()
             }),
             &mut buffer,
         );
@@ -367,6 +963,10 @@ mod tests {
         run_command(
             &Commands::Print(PrintArgs {
                 format: Some("{name} = {value}".to_owned()),
+                header: None,
+                footer: None,
+                separator: None,
+                reveal: false,
             }),
             None,
             &mut buffer,
@@ -379,6 +979,53 @@ mod tests {
         unsafe { env::remove_var("TEST_PRINT_RUN") };
     }
 
+    #[test]
+    fn test_print_layout_precedence() {
+        let opt = PrintArgs {
+            format: Some("{name}".to_owned()),
+            header: None,
+            footer: None,
+            separator: None,
+            reveal: false,
+        };
+        let config = PrintFormat::Layout {
+            entry: Some("{value}".to_owned()),
+            header: Some("start".to_owned()),
+            footer: None,
+            separator: Some(",".to_owned()),
+        };
+        assert_eq!(
+            print_layout(&opt, Some(config)),
+            Layout {
+                entry: "{name}".to_owned(),
+                header: Some("start".to_owned()),
+                footer: None,
+                separator: ",".to_owned(),
+            }
+        );
+        assert_eq!(print_layout(&opt, None), Layout::new("{name}"));
+    }
+
+    #[test]
+    fn test_run_command_print_invalid_filter() {
+        let mut buffer = vec![];
+        assert_eq!(
+            run_command(
+                &Commands::Print(PrintArgs {
+                    format: Some("{value|unknown}".to_owned()),
+                    header: None,
+                    footer: None,
+                    separator: None,
+                    reveal: false,
+                }),
+                None,
+                &mut buffer,
+            ),
+            ExitCode::FAILURE
+        );
+        assert!(buffer.is_empty());
+    }
+
     #[test]
     fn test_run_command_delete() {
         init();
@@ -388,6 +1035,8 @@ mod tests {
             &Commands::Delete(DeleteArgs {
                 key: "TEST_DELETE_RUN".to_string(),
                 global: false,
+                dry_run: false,
+                validation: None,
                 process: vec![],
             }),
             None,
@@ -407,6 +1056,8 @@ mod tests {
             &Commands::Load(LoadArgs {
                 file: temp_file.path().to_string_lossy().to_string(),
                 global: false,
+                dry_run: false,
+                validation: None,
                 process: vec![],
             }),
             None,
@@ -424,7 +1075,18 @@ mod tests {
         unsafe { env::set_var("TEST_PRINT_VAR", "test_value") };
 
         let mut buffer = vec![];
-        print_env(&PrintArgs { format: None }, &mut buffer);
+        print_env(
+            &PrintArgs {
+                format: None,
+                header: None,
+                footer: None,
+                separator: None,
+                reveal: false,
+            },
+            None,
+            &mut buffer,
+        )
+        .unwrap();
         assert!(
             String::from_utf8(buffer)
                 .unwrap()
@@ -443,7 +1105,18 @@ mod tests {
         unsafe { env::set_var("TEST_VAR_2", "value2") };
 
         let mut buffer = vec![];
-        print_env(&PrintArgs { format: None }, &mut buffer);
+        print_env(
+            &PrintArgs {
+                format: None,
+                header: None,
+                footer: None,
+                separator: None,
+                reveal: false,
+            },
+            None,
+            &mut buffer,
+        )
+        .unwrap();
         assert!(
             String::from_utf8(buffer.clone())
                 .unwrap()
@@ -466,12 +1139,19 @@ mod tests {
         unsafe { env::set_var("TEST_GET_VAR", "test_value") };
 
         let args = GetArgs {
-            key: "TEST_GET_VAR".to_string(),
+            keys: vec!["TEST_GET_VAR".to_string()],
             no_similar_names: false,
+            raw: false,
+            quoted: false,
+            default: None,
+            format: None,
+            json: false,
+            ignore_case: false,
+            fuzzy: false,
         };
         let mut buffer = vec![];
 
-        let result = get(&args, &mut buffer);
+        let result = get(&args, None, &mut buffer);
         assert!(result.is_ok());
         assert!(
             String::from_utf8(buffer)
@@ -488,12 +1168,19 @@ mod tests {
         unsafe { env::set_var("TEST_SIMILAR", "value") };
 
         let args = GetArgs {
-            key: "TEST_SMILAR".to_string(), // Intentional typo
+            keys: vec!["TEST_SMILAR".to_string()], // Intentional typo
             no_similar_names: false,
+            raw: false,
+            quoted: false,
+            default: None,
+            format: None,
+            json: false,
+            ignore_case: false,
+            fuzzy: false,
         };
 
         let mut buffer = vec![];
-        let result = get(&args, &mut buffer);
+        let result = get(&args, None, &mut buffer);
         assert!(result.is_err());
         match result.unwrap_err() {
             ErrorKind::CannotFindVariable(var, no_similar) => {
@@ -510,12 +1197,19 @@ mod tests {
     fn test_get_nonexistent_variable_no_similar_names() {
         init();
         let args = GetArgs {
-            key: "NONEXISTENT_VAR".to_string(),
+            keys: vec!["NONEXISTENT_VAR".to_string()],
             no_similar_names: true,
+            raw: false,
+            quoted: false,
+            default: None,
+            format: None,
+            json: false,
+            ignore_case: false,
+            fuzzy: false,
         };
 
         let mut buffer = vec![];
-        let result = get(&args, &mut buffer);
+        let result = get(&args, None, &mut buffer);
         assert!(result.is_err());
         match result.unwrap_err() {
             ErrorKind::CannotFindVariable(var, no_similar) => {
@@ -532,27 +1226,373 @@ mod tests {
         unsafe { env::set_var("TEST_SPECIAL_$#@", "special_value") };
 
         let args = GetArgs {
-            key: "TEST_SPECIAL_$#@".to_string(),
+            keys: vec!["TEST_SPECIAL_$#@".to_string()],
             no_similar_names: false,
+            raw: false,
+            quoted: false,
+            default: None,
+            format: None,
+            json: false,
+            ignore_case: false,
+            fuzzy: false,
         };
 
         let mut buffer = vec![];
-        let result = get(&args, &mut buffer);
+        let result = get(&args, None, &mut buffer);
         assert!(result.is_ok());
 
         unsafe { env::remove_var("TEST_SPECIAL_$#@") };
     }
 
+    #[test]
+    fn test_print_similar_names_with_config() {
+        unsafe { env::set_var("TEST_SUGGEST_ALPHA", "1") };
+        unsafe { env::set_var("TEST_SUGGEST_ALPHB", "1") };
+        let config = Config {
+            suggestion_threshold: Some(0.8),
+            suggestion_count: Some(1),
+            ..Default::default()
+        };
+        let mut buffer = vec![];
+        print_similar_names("TEST_SUGGEST_ALPH", Some(&config), &mut buffer);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "Did you mean:\n  TEST_SUGGEST_ALPHA\n"
+        );
+        unsafe { env::remove_var("TEST_SUGGEST_ALPHA") };
+        unsafe { env::remove_var("TEST_SUGGEST_ALPHB") };
+    }
+
+    #[test]
+    fn test_run_command_delete_suggests_similar_names_on_stderr() {
+        unsafe { env::set_var("TEST_DELETE_SUGGESTION", "1") };
+        let mut buffer = vec![];
+        run_command(
+            &Commands::Delete(DeleteArgs {
+                key: "TEST_DELETE_SUGESTION".to_string(), // Intentional typo
+                global: false,
+                dry_run: false,
+                validation: None,
+                process: vec![],
+            }),
+            None,
+            &mut buffer,
+        );
+        assert!(buffer.is_empty());
+        assert!(env::var("TEST_DELETE_SUGGESTION").is_ok());
+        unsafe { env::remove_var("TEST_DELETE_SUGGESTION") };
+    }
+
+    #[test]
+    fn test_plan_set_with_validation_profile() {
+        let args = SetArgs {
+            key: "MY-VAR".to_string(),
+            value: "value".to_string(),
+            global: false,
+            dry_run: true,
+            validation: Some(ValidationProfile::Posix),
+            process: vec![],
+        };
+        assert!(matches!(
+            plan_set(&args),
+            Err(ErrorKind::NameValidationError(_))
+        ));
+        let args = SetArgs {
+            validation: None,
+            ..args
+        };
+        assert!(plan_set(&args).is_ok());
+    }
+
+    #[test]
+    fn test_plan_set_value_with_nul() {
+        let args = SetArgs {
+            key: "TEST_SET_NUL".to_string(),
+            value: "a\0b".to_string(),
+            global: false,
+            dry_run: true,
+            validation: None,
+            process: vec![],
+        };
+        assert!(matches!(
+            plan_set(&args),
+            Err(ErrorKind::ValueValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_run_command_validation_from_config() {
+        let config = Config {
+            validation: Some(ValidationProfile::Portable),
+            ..Default::default()
+        };
+        let mut buffer = vec![];
+        assert_eq!(
+            run_command(
+                &Commands::Set(SetArgs {
+                    key: "test_lowercase".to_string(),
+                    value: "value".to_string(),
+                    global: false,
+                    dry_run: true,
+                    validation: None,
+                    process: vec![],
+                }),
+                Some(config),
+                &mut buffer,
+            ),
+            ExitCode::FAILURE
+        );
+    }
+
+    #[test]
+    fn test_with_validation_keeps_explicit_profile() {
+        let command = Commands::Delete(DeleteArgs {
+            key: "KEY".to_string(),
+            global: false,
+            dry_run: false,
+            validation: Some(ValidationProfile::Windows),
+            process: vec![],
+        });
+        assert_eq!(with_validation(&command, ValidationProfile::Posix), None);
+        assert_eq!(
+            with_validation(&Commands::History, ValidationProfile::Posix),
+            None
+        );
+    }
+
+    fn get_args(keys: &[&str]) -> GetArgs {
+        GetArgs {
+            keys: keys.iter().map(|key| key.to_string()).collect(),
+            no_similar_names: false,
+            raw: false,
+            quoted: false,
+            default: None,
+            format: None,
+            json: false,
+            ignore_case: false,
+            fuzzy: false,
+        }
+    }
+
+    #[test]
+    fn test_get_ignore_case() {
+        unsafe { env::set_var("TEST_GET_IGNORE_CASE", "value") };
+        let args = GetArgs {
+            ignore_case: true,
+            ..get_args(&["test_get_ignore_case"])
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "\"value\"\n");
+        unsafe { env::remove_var("TEST_GET_IGNORE_CASE") };
+    }
+
+    #[test]
+    fn test_get_ignore_case_ambiguous() {
+        unsafe { env::set_var("TEST_GET_AMBIGUOUS", "upper") };
+        unsafe { env::set_var("test_get_ambiguous", "lower") };
+        let args = GetArgs {
+            ignore_case: true,
+            ..get_args(&["Test_Get_Ambiguous"])
+        };
+        let mut buffer = vec![];
+        let result = get(&args, None, &mut buffer);
+        assert!(matches!(
+            result,
+            Err(ErrorKind::AmbiguousVariable(_, candidates))
+                if candidates == vec!["TEST_GET_AMBIGUOUS", "test_get_ambiguous"]
+        ));
+        assert!(buffer.is_empty());
+        unsafe { env::remove_var("TEST_GET_AMBIGUOUS") };
+        unsafe { env::remove_var("test_get_ambiguous") };
+    }
+
+    #[test]
+    fn test_get_ignore_case_prefers_exact_match() {
+        unsafe { env::set_var("TEST_GET_EXACT_CASE", "upper") };
+        unsafe { env::set_var("test_get_exact_case", "lower") };
+        let args = GetArgs {
+            ignore_case: true,
+            ..get_args(&["test_get_exact_case"])
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "\"lower\"\n");
+        unsafe { env::remove_var("TEST_GET_EXACT_CASE") };
+        unsafe { env::remove_var("test_get_exact_case") };
+    }
+
+    #[test]
+    fn test_get_fuzzy() {
+        unsafe { env::set_var("TEST_GET_FUZZY_QWXZ", "value") };
+        let args = GetArgs {
+            fuzzy: true,
+            format: Some("{name}={value}".to_string()),
+            ..get_args(&["TEST_GET_FUZY_QWXZ"]) // Intentional typo
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "TEST_GET_FUZZY_QWXZ=value\n"
+        );
+        unsafe { env::remove_var("TEST_GET_FUZZY_QWXZ") };
+    }
+
+    #[test]
+    fn test_get_fuzzy_uses_configured_threshold() {
+        unsafe { env::set_var("TEST_GET_THRESHOLD_QWXZ", "value") };
+        let args = GetArgs {
+            fuzzy: true,
+            ..get_args(&["TEST_GET_THRESHOLD_QWX"])
+        };
+        let config = Config {
+            suggestion_threshold: Some(0.99),
+            ..Default::default()
+        };
+        assert!(matches!(
+            get(&args, Some(&config), vec![]),
+            Err(ErrorKind::CannotFindVariable(_, _))
+        ));
+        assert!(get(&args, None, vec![]).is_ok());
+        unsafe { env::remove_var("TEST_GET_THRESHOLD_QWXZ") };
+    }
+
+    #[test]
+    fn test_get_raw() {
+        unsafe { env::set_var("TEST_GET_RAW", "C:\\foo \"bar\"") };
+        let args = GetArgs {
+            raw: true,
+            ..get_args(&["TEST_GET_RAW"])
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "C:\\foo \"bar\"\n");
+        unsafe { env::remove_var("TEST_GET_RAW") };
+    }
+
+    #[test]
+    fn test_get_multiple_keys_with_default() {
+        unsafe { env::set_var("TEST_GET_MULTI_1", "one") };
+        let args = GetArgs {
+            default: Some("fallback".to_string()),
+            ..get_args(&["TEST_GET_MULTI_1", "TEST_GET_MULTI_MISSING"])
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "\"one\"\n\"fallback\"\n"
+        );
+        unsafe { env::remove_var("TEST_GET_MULTI_1") };
+    }
+
+    #[test]
+    fn test_get_multiple_keys_missing_prints_nothing() {
+        unsafe { env::set_var("TEST_GET_PARTIAL", "one") };
+        let mut buffer = vec![];
+        let result = get(
+            &get_args(&["TEST_GET_PARTIAL", "TEST_GET_PARTIAL_MISSING"]),
+            None,
+            &mut buffer,
+        );
+        assert!(matches!(
+            result,
+            Err(ErrorKind::CannotFindVariable(key, false)) if key == "TEST_GET_PARTIAL_MISSING"
+        ));
+        assert!(buffer.is_empty());
+        unsafe { env::remove_var("TEST_GET_PARTIAL") };
+    }
+
+    #[test]
+    fn test_get_format() {
+        unsafe { env::set_var("TEST_GET_FORMAT", "value") };
+        let args = GetArgs {
+            format: Some("{name}={value}".to_string()),
+            ..get_args(&["TEST_GET_FORMAT"])
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "TEST_GET_FORMAT=value\n"
+        );
+        unsafe { env::remove_var("TEST_GET_FORMAT") };
+    }
+
+    #[test]
+    fn test_get_json() {
+        unsafe { env::set_var("TEST_GET_JSON_B", "with \"quotes\"") };
+        unsafe { env::set_var("TEST_GET_JSON_A", "plain") };
+        let args = GetArgs {
+            json: true,
+            ..get_args(&["TEST_GET_JSON_B", "TEST_GET_JSON_A"])
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"TEST_GET_JSON_B\":\"with \\\"quotes\\\"\",\"TEST_GET_JSON_A\":\"plain\"}\n"
+        );
+
+        let args = GetArgs {
+            json: true,
+            ..get_args(&["TEST_GET_JSON_A", "TEST_GET_JSON_A"])
+        };
+        let mut buffer = vec![];
+        get(&args, None, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"TEST_GET_JSON_A\":\"plain\"}\n"
+        );
+        unsafe { env::remove_var("TEST_GET_JSON_B") };
+        unsafe { env::remove_var("TEST_GET_JSON_A") };
+    }
+
+    #[test]
+    fn test_run_command_get_raw_from_config() {
+        unsafe { env::set_var("TEST_GET_RAW_CONFIG", "value") };
+        let config = Config {
+            get_raw: Some(true),
+            ..Default::default()
+        };
+        let mut buffer = vec![];
+        run_command(
+            &Commands::Get(get_args(&["TEST_GET_RAW_CONFIG"])),
+            Some(config),
+            &mut buffer,
+        );
+        assert_eq!(String::from_utf8(buffer).unwrap(), "value\n");
+
+        let config = Config {
+            get_raw: Some(true),
+            ..Default::default()
+        };
+        let mut buffer = vec![];
+        run_command(
+            &Commands::Get(GetArgs {
+                quoted: true,
+                ..get_args(&["TEST_GET_RAW_CONFIG"])
+            }),
+            Some(config),
+            &mut buffer,
+        );
+        assert_eq!(String::from_utf8(buffer).unwrap(), "\"value\"\n");
+        unsafe { env::remove_var("TEST_GET_RAW_CONFIG") };
+    }
+
     #[test]
     fn test_set_valid_variable() {
         let args = SetArgs {
             key: "TEST_SET_VAR".to_string(),
             value: "test_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = set(&args);
+        let result = set(&args, &temp_journal());
         assert!(result.is_ok());
 
         assert_eq!(env::var("TEST_SET_VAR").unwrap(), "test_value");
@@ -565,10 +1605,12 @@ mod tests {
             key: "INVALID NAME".to_string(), // Space in name
             value: "test_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = set(&args);
+        let result = set(&args, &temp_journal());
         assert!(result.is_err());
         match result.unwrap_err() {
             ErrorKind::NameValidationError(err) => {
@@ -584,10 +1626,12 @@ mod tests {
             key: "".to_string(),
             value: "test_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = set(&args);
+        let result = set(&args, &temp_journal());
         assert!(result.is_err());
         match result.unwrap_err() {
             ErrorKind::NameValidationError(err) => {
@@ -612,10 +1656,12 @@ mod tests {
             key: "TEST_PROCESS_VAR".to_string(),
             value: "test_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: test_cmd,
         };
 
-        let result = set(&args);
+        let result = set(&args, &temp_journal());
         assert!(result.is_ok(), "Expected Ok result, got {:?}", result);
         assert_eq!(env::var("TEST_PROCESS_VAR").unwrap(), "test_value");
         unsafe { env::remove_var("TEST_PROCESS_VAR") };
@@ -629,10 +1675,12 @@ mod tests {
             key: "TEST_OVERWRITE".to_string(),
             value: "new_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = set(&args);
+        let result = set(&args, &temp_journal());
         assert!(result.is_ok());
 
         assert_eq!(env::var("TEST_OVERWRITE").unwrap(), "new_value");
@@ -645,10 +1693,12 @@ mod tests {
             key: "TEST_ADD_NEW".to_string(),
             value: "new_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = add(&args);
+        let result = add(&args, &temp_journal());
         assert!(result.is_ok());
         assert_eq!(env::var("TEST_ADD_NEW").unwrap(), "new_value");
         unsafe { env::remove_var("TEST_ADD_NEW") };
@@ -662,10 +1712,12 @@ mod tests {
             key: "TEST_ADD_EXISTING".to_string(),
             value: "appended".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = add(&args);
+        let result = add(&args, &temp_journal());
         assert!(result.is_ok());
         assert_eq!(env::var("TEST_ADD_EXISTING").unwrap(), "existing_appended");
         unsafe { env::remove_var("TEST_ADD_EXISTING") };
@@ -677,10 +1729,12 @@ mod tests {
             key: "INVALID NAME".to_string(),
             value: "test_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = add(&args);
+        let result = add(&args, &temp_journal());
         assert!(result.is_err());
         match result.unwrap_err() {
             ErrorKind::NameValidationError(err) => {
@@ -698,10 +1752,12 @@ mod tests {
             key: "TEST_ADD_EMPTY".to_string(),
             value: "".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = add(&args);
+        let result = add(&args, &temp_journal());
         assert!(result.is_ok());
         assert_eq!(env::var("TEST_ADD_EMPTY").unwrap(), "existing");
         unsafe { env::remove_var("TEST_ADD_EMPTY") };
@@ -719,11 +1775,13 @@ mod tests {
             key: "TEST_ADD_PROCESS".to_string(),
             value: "_value".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: test_cmd,
         };
 
         unsafe { env::set_var("TEST_ADD_PROCESS", "initial") };
-        let result = add(&args);
+        let result = add(&args, &temp_journal());
         assert!(result.is_ok(), "Expected Ok result, got {:?}", result);
         assert_eq!(env::var("TEST_ADD_PROCESS").unwrap(), "initial_value");
         unsafe { env::remove_var("TEST_ADD_PROCESS") };
@@ -736,10 +1794,12 @@ mod tests {
         let args = DeleteArgs {
             key: "TEST_DELETE_VAR".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = delete(&args);
+        let result = delete(&args, &temp_journal());
         assert!(result.is_ok());
         assert!(env::var("TEST_DELETE_VAR").is_err());
     }
@@ -749,10 +1809,12 @@ mod tests {
         let args = DeleteArgs {
             key: "NONEXISTENT_VAR".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = delete(&args);
+        let result = delete(&args, &temp_journal());
         // Should succeed even if variable doesn't exist
         assert!(result.is_ok());
     }
@@ -762,10 +1824,12 @@ mod tests {
         let args = DeleteArgs {
             key: "INVALID NAME".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = delete(&args);
+        let result = delete(&args, &temp_journal());
         assert!(result.is_err());
         match result.unwrap_err() {
             ErrorKind::NameValidationError(err) => {
@@ -788,10 +1852,12 @@ mod tests {
         let args = DeleteArgs {
             key: "TEST_DELETE_PROCESS".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: test_cmd,
         };
 
-        let result = delete(&args);
+        let result = delete(&args, &temp_journal());
         assert!(result.is_ok(), "Expected Ok result, got {:?}", result);
         assert!(env::var("TEST_DELETE_PROCESS").is_err());
     }
@@ -801,10 +1867,12 @@ mod tests {
         let args = DeleteArgs {
             key: "".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = delete(&args);
+        let result = delete(&args, &temp_journal());
         assert!(result.is_err());
         match result.unwrap_err() {
             ErrorKind::NameValidationError(err) => {
@@ -822,10 +1890,12 @@ mod tests {
         let args = LoadArgs {
             file: temp_file.path().to_string_lossy().to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = load(&args);
+        let result = load(&args, &temp_journal());
         assert!(result.is_ok());
         assert_eq!(env::var("TEST_VAR").unwrap(), "test_value");
         assert_eq!(env::var("OTHER_VAR").unwrap(), "other_value");
@@ -839,10 +1909,12 @@ mod tests {
         let args = LoadArgs {
             file: "nonexistent.env".to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = load(&args);
+        let result = load(&args, &temp_journal());
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ErrorKind::FileError(_)));
     }
@@ -856,10 +1928,12 @@ mod tests {
         let args = LoadArgs {
             file: temp_file.path().to_string_lossy().to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = load(&args);
+        let result = load(&args, &temp_journal());
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ErrorKind::ParsingError(_)));
     }
@@ -870,18 +1944,25 @@ mod tests {
         writeln!(temp_file, "TEST_PROCESS_VAR=process_value").unwrap();
 
         #[cfg(windows)]
-        let cmd = vec!["cmd".to_string(), "/C".to_string(), "echo".to_string(), "test".to_string()];
+        let cmd = vec![
+            "cmd".to_string(),
+            "/C".to_string(),
+            "echo".to_string(),
+            "test".to_string(),
+        ];
         #[cfg(not(windows))]
         let cmd = vec!["echo".to_string(), "test".to_string()];
 
         let args = LoadArgs {
             file: temp_file.path().to_string_lossy().to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: cmd,
         };
 
         // First verify the variable is set correctly
-        let result = load(&args);
+        let result = load(&args, &temp_journal());
         assert!(result.is_ok(), "Load operation failed: {:?}", result);
     }
 
@@ -892,10 +1973,12 @@ mod tests {
         let args = LoadArgs {
             file: temp_file.path().to_string_lossy().to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = load(&args);
+        let result = load(&args, &temp_journal());
         assert!(result.is_ok());
     }
 
@@ -907,10 +1990,12 @@ mod tests {
         let args = LoadArgs {
             file: temp_file.path().to_string_lossy().to_string(),
             global: false,
+            dry_run: false,
+            validation: None,
             process: vec![],
         };
 
-        let result = load(&args);
+        let result = load(&args, &temp_journal());
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ErrorKind::ParsingError(_)));
     }
@@ -922,7 +2007,13 @@ mod tests {
         let mut buffer = vec![];
         assert_eq!(
             run_command(
-                &Commands::Print(PrintArgs { format: None }),
+                &Commands::Print(PrintArgs {
+                    format: None,
+                    header: None,
+                    footer: None,
+                    separator: None,
+                    reveal: false,
+                }),
                 None,
                 &mut buffer
             ),
@@ -944,8 +2035,15 @@ mod tests {
         assert_eq!(
             run_command(
                 &Commands::Get(GetArgs {
-                    key: "TEST_SMILAR_VAR".to_string(), // Intentional typo
+                    keys: vec!["TEST_SMILAR_VAR".to_string()], // Intentional typo
                     no_similar_names: false,
+                    raw: false,
+                    quoted: false,
+                    default: None,
+                    format: None,
+                    json: false,
+                    ignore_case: false,
+                    fuzzy: false,
                 }),
                 None,
                 &mut buffer
@@ -969,6 +2067,8 @@ mod tests {
                 key: "TEST_SET_RUN".to_string(),
                 value: "test_value".to_string(),
                 global: false,
+                dry_run: false,
+                validation: None,
                 process: test_cmd,
             }),
             None,
@@ -987,6 +2087,8 @@ mod tests {
                     key: "INVALID NAME".to_string(),
                     value: "test_value".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: vec![],
                 }),
                 None,
@@ -1007,6 +2109,8 @@ mod tests {
                     key: "TEST_ADD_EXISTING".to_string(),
                     value: "appended".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: vec![],
                 }),
                 None,
@@ -1028,6 +2132,8 @@ mod tests {
                     key: "INVALID NAME".to_string(),
                     value: "test_value".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: vec![],
                 }),
                 None,
@@ -1046,6 +2152,8 @@ mod tests {
                 &Commands::Delete(DeleteArgs {
                     key: "NONEXISTENT_VAR".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: vec![],
                 }),
                 None,
@@ -1064,6 +2172,8 @@ mod tests {
                 &Commands::Load(LoadArgs {
                     file: "nonexistent.env".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: vec![],
                 }),
                 None,
@@ -1089,6 +2199,8 @@ mod tests {
             &Commands::Load(LoadArgs {
                 file: temp_file.path().to_string_lossy().to_string(),
                 global: false,
+                dry_run: false,
+                validation: None,
                 process: test_cmd,
             }),
             None,
@@ -1100,41 +2212,255 @@ mod tests {
     }
 
     #[test]
-    fn test_run_command_global_operations() {
+    fn test_global_operations() {
         init();
-        let mut buffer = vec![];
-        let result = run_command(
-            &Commands::Set(SetArgs {
+        let journal = temp_journal();
+        let result = set(
+            &SetArgs {
                 key: "TEST_GLOBAL".to_string(),
                 value: "test_value".to_string(),
                 global: true,
+                dry_run: false,
+                validation: None,
                 process: vec![],
-            }),
-            None,
-            &mut buffer,
+            },
+            &journal,
         );
         // Test passes if operation succeeds OR fails with permission error
         match result {
-            ExitCode::SUCCESS => {
+            Ok(_) => {
                 assert_eq!(env::var("TEST_GLOBAL").unwrap(), "test_value");
-                assert_eq!(
-                    run_command(
-                        &Commands::Delete(DeleteArgs {
-                            key: "TEST_GLOBAL".to_string(),
-                            global: true,
-                            process: vec![],
-                        }),
-                        None,
-                        &mut buffer
-                    ),
-                    ExitCode::SUCCESS
+                let result = delete(
+                    &DeleteArgs {
+                        key: "TEST_GLOBAL".to_string(),
+                        global: true,
+                        dry_run: false,
+                        validation: None,
+                        process: vec![],
+                    },
+                    &journal,
                 );
+                assert!(result.is_ok());
+                assert_eq!(history::get_changes(&journal).unwrap().len(), 2);
             }
-            ExitCode::FAILURE => {} // Expected on non-admin
-            _ => panic!("Unexpected exit code"),
+            Err(ErrorKind::CannotSetVariableGlobally(_)) => {} // Expected on non-admin
+            Err(err) => panic!("Unexpected error: {}", err),
         }
     }
 
+    #[test]
+    fn test_run_command_set_dry_run() {
+        init();
+        unsafe { env::set_var("TEST_DRY_RUN_SET", "old") };
+        let mut buffer = vec![];
+        let result = run_command(
+            &Commands::Set(SetArgs {
+                key: "TEST_DRY_RUN_SET".to_string(),
+                value: "new".to_string(),
+                global: false,
+                dry_run: true,
+                validation: None,
+                process: vec!["false".to_string()],
+            }),
+            None,
+            &mut buffer,
+        );
+        assert_eq!(result, ExitCode::SUCCESS);
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("change TEST_DRY_RUN_SET: \"old\" -> \"new\""));
+        assert!(output.contains("Would run: false"));
+        assert_eq!(env::var("TEST_DRY_RUN_SET").unwrap(), "old");
+        unsafe { env::remove_var("TEST_DRY_RUN_SET") };
+    }
+
+    #[test]
+    fn test_run_command_delete_dry_run() {
+        init();
+        unsafe { env::set_var("TEST_DRY_RUN_DELETE", "value") };
+        let mut buffer = vec![];
+        let result = run_command(
+            &Commands::Delete(DeleteArgs {
+                key: "TEST_DRY_RUN_DELETE".to_string(),
+                global: false,
+                dry_run: true,
+                validation: None,
+                process: vec![],
+            }),
+            None,
+            &mut buffer,
+        );
+        assert_eq!(result, ExitCode::SUCCESS);
+        assert!(
+            String::from_utf8(buffer)
+                .unwrap()
+                .contains("remove TEST_DRY_RUN_DELETE (was \"value\")")
+        );
+        assert_eq!(env::var("TEST_DRY_RUN_DELETE").unwrap(), "value");
+        unsafe { env::remove_var("TEST_DRY_RUN_DELETE") };
+    }
+
+    #[test]
+    fn test_plan_load() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "TEST_PLAN_LOAD=value").unwrap();
+        let args = LoadArgs {
+            file: temp_file.path().to_string_lossy().to_string(),
+            global: false,
+            dry_run: true,
+            validation: None,
+            process: vec![],
+        };
+        assert_eq!(
+            plan_load(&args).unwrap(),
+            vec![PlannedChange {
+                key: "TEST_PLAN_LOAD".to_string(),
+                old: None,
+                new: Some("value".to_string()),
+            }]
+        );
+        assert!(env::var("TEST_PLAN_LOAD").is_err());
+    }
+
+    #[test]
+    fn test_plan_add_global_based_on_session() {
+        unsafe { env::set_var("TEST_PLAN_ADD_GLOBAL", "first") };
+        let args = AddArgs {
+            key: "TEST_PLAN_ADD_GLOBAL".to_string(),
+            value: ":second".to_string(),
+            global: true,
+            dry_run: false,
+            validation: None,
+            process: vec![],
+        };
+        assert_eq!(
+            plan_add(&args).unwrap(),
+            vec![PlannedChange {
+                key: "TEST_PLAN_ADD_GLOBAL".to_string(),
+                old: Some("first".to_string()),
+                new: Some("first:second".to_string()),
+            }]
+        );
+        unsafe { env::remove_var("TEST_PLAN_ADD_GLOBAL") };
+    }
+
+    #[test]
+    fn test_print_plan_invalid_name() {
+        let args = AddArgs {
+            key: "INVALID NAME".to_string(),
+            value: "value".to_string(),
+            global: false,
+            dry_run: true,
+            validation: None,
+            process: vec![],
+        };
+        let result = print_plan(plan_add(&args), false, &[], vec![]);
+        assert!(matches!(result, Err(ErrorKind::NameValidationError(_))));
+    }
+
+    #[test]
+    fn test_shell_variables() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "FROM_FILE=file\nOVERRIDDEN=file").unwrap();
+        let args = ShellArgs {
+            file: Some(temp_file.path().to_string_lossy().to_string()),
+            set: vec!["OVERRIDDEN=set".to_string(), "WITH_EQUALS=a=b".to_string()],
+            nested: false,
+            validation: None,
+        };
+        let variables = shell_variables(&args).unwrap();
+        assert_eq!(
+            variables,
+            vec![
+                ("FROM_FILE".to_string(), "file".to_string()),
+                ("OVERRIDDEN".to_string(), "file".to_string()),
+                ("OVERRIDDEN".to_string(), "set".to_string()),
+                ("WITH_EQUALS".to_string(), "a=b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_variables_invalid_set() {
+        let args = ShellArgs {
+            file: None,
+            set: vec!["NO_VALUE".to_string()],
+            nested: false,
+            validation: None,
+        };
+        assert!(matches!(
+            shell_variables(&args),
+            Err(ErrorKind::ParsingError(_))
+        ));
+    }
+
+    #[test]
+    fn test_shell_variables_validates_file_keys() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "lower_case=file").unwrap();
+        let args = ShellArgs {
+            file: Some(temp_file.path().to_string_lossy().to_string()),
+            set: vec![],
+            nested: false,
+            validation: Some(ValidationProfile::Portable),
+        };
+        assert!(matches!(
+            shell_variables(&args),
+            Err(ErrorKind::NameValidationError(_))
+        ));
+        assert_eq!(
+            with_validation(
+                &Commands::Shell(ShellArgs {
+                    validation: None,
+                    ..args.clone()
+                }),
+                ValidationProfile::Portable
+            ),
+            Some(Commands::Shell(args))
+        );
+    }
+
+    #[test]
+    fn test_format_history_value() {
+        assert_eq!(
+            format_history_value(&Some("value".to_string())),
+            "\"value\""
+        );
+        assert_eq!(format_history_value(&None), "(unset)");
+    }
+
+    #[test]
+    fn test_print_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = dir.path().join("history.toml");
+        history::record_change(&journal, "FIRST", None, Some("1")).unwrap();
+        history::record_change(&journal, "SECOND", Some("old".to_string()), None).unwrap();
+        let mut buffer = vec![];
+        print_history(&journal, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("SECOND: \"old\" -> (unset)"));
+        assert!(lines[1].ends_with("FIRST: (unset) -> \"1\""));
+    }
+
+    #[test]
+    fn test_run_command_global_get_nonexistent() {
+        init();
+        let mut buffer = vec![];
+        assert_eq!(
+            run_command(
+                &Commands::Global(GlobalArgs {
+                    command: GlobalCommands::Get(GlobalKeyArgs {
+                        key: "TEST_GLOBAL_NONEXISTENT_fjqwke".to_string(),
+                    }),
+                }),
+                None,
+                &mut buffer
+            ),
+            ExitCode::FAILURE
+        );
+    }
+
     #[test]
     fn test_run_command_delete_with_process_fail() {
         init();
@@ -1142,7 +2468,12 @@ mod tests {
 
         let mut buffer = vec![];
         #[cfg(windows)]
-        let failing_command = vec!["cmd".to_string(), "/C".to_string(), "exit".to_string(), "1".to_string()];
+        let failing_command = vec![
+            "cmd".to_string(),
+            "/C".to_string(),
+            "exit".to_string(),
+            "1".to_string(),
+        ];
         #[cfg(not(windows))]
         let failing_command = vec!["false".to_string()];
         assert_eq!(
@@ -1150,6 +2481,8 @@ mod tests {
                 &Commands::Delete(DeleteArgs {
                     key: "TEST_DELETE_PROC_FAIL".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: failing_command,
                 }),
                 None,
@@ -1170,6 +2503,8 @@ mod tests {
                 &Commands::Delete(DeleteArgs {
                     key: "INVALID NAME".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: vec![],
                 }),
                 None,
@@ -1188,6 +2523,8 @@ mod tests {
                 &Commands::Delete(DeleteArgs {
                     key: "".to_string(),
                     global: false,
+                    dry_run: false,
+                    validation: None,
                     process: vec![],
                 }),
                 None,
@@ -1202,7 +2539,8 @@ mod tests {
         init();
         let mut buffer = vec![];
         let config = Config {
-            print_format: Some("{name}={value}".to_string()),
+            print_format: Some(PrintFormat::Entry("{name}={value}".to_string())),
+            ..Default::default()
         };
         assert_eq!(
             run_command(&Commands::InitConfig, Some(config), &mut buffer),
@@ -1214,7 +2552,15 @@ mod tests {
     fn test_interactrive_mode() {
         init();
         let mut buffer = vec![];
-        run_command(&Commands::Interactive, None, &mut buffer);
+        run_command(
+            &Commands::Interactive(InteractiveArgs {
+                file: None,
+                export: None,
+                validation: None,
+            }),
+            None,
+            &mut buffer,
+        );
     }
 
     #[test]
@@ -0,0 +1,244 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use crate::models::Cli;
+
+/// Commands which take variable name as first positional argument
+const KEY_COMMANDS: [&str; 3] = ["get", "add", "delete"];
+
+/// Commands which take dotenv file with --file option
+const FILE_COMMANDS: [&str; 4] = ["load", "shell", "render", "audit"];
+
+/// Write completion script for given shell
+pub fn generate_completions<W: Write>(shell: Shell, mut buffer: W) {
+    let mut command = Cli::command();
+    let mut script = vec![];
+    clap_complete::generate(shell, &mut command, "envfetch", &mut script);
+    let script = String::from_utf8_lossy(&script);
+    // Ask envfetch for dynamic candidates first and fall back to static completions
+    let script = match shell {
+        Shell::Bash => format!(
+            r#"{script}
+_envfetch_dynamic() {{
+    local candidates
+    candidates="$(envfetch __complete -- "${{COMP_WORDS[@]:1:COMP_CWORD}}" 2>/dev/null)"
+    if [[ -n "${{candidates}}" ]]; then
+        local IFS=$'\n'
+        COMPREPLY=( ${{candidates}} )
+        return 0
+    fi
+    _envfetch "$@"
+}}
+complete -F _envfetch_dynamic -o bashdefault -o default envfetch
+"#
+        ),
+        Shell::Zsh => format!(
+            r#"{script}
+_envfetch_dynamic() {{
+    local -a candidates
+    candidates=(${{(f)"$(envfetch __complete -- "${{(@)words[2,CURRENT]}}" 2>/dev/null)"}})
+    if (( ${{#candidates}} )); then
+        compadd -a candidates
+    else
+        _envfetch "$@"
+    fi
+}}
+compdef _envfetch_dynamic envfetch
+"#
+        ),
+        Shell::Fish => format!(
+            r#"{script}
+complete -c envfetch -a '(envfetch __complete -- (commandline -opc)[2..-1] (commandline -ct))'
+"#
+        ),
+        Shell::PowerShell => script.replacen(
+            "param($wordToComplete, $commandAst, $cursorPosition)\n",
+            r#"param($wordToComplete, $commandAst, $cursorPosition)
+
+    $envfetchWords = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() })
+    if ($wordToComplete -eq '') { $envfetchWords += '' }
+    $envfetchCandidates = @(& envfetch __complete -- @envfetchWords)
+    if ($envfetchCandidates.Count -gt 0) {
+        return $envfetchCandidates | ForEach-Object {
+            [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+        }
+    }
+"#,
+            1,
+        ),
+        Shell::Elvish => format!(
+            r#"{script}
+var envfetch-static = $edit:completion:arg-completer[envfetch]
+set edit:completion:arg-completer[envfetch] = {{|@words|
+    var candidates = [(envfetch __complete -- $@words[1..])]
+    if (> (count $candidates) 0) {{
+        put $@candidates
+    }} else {{
+        $envfetch-static $@words
+    }}
+}}
+"#
+        ),
+        _ => script.into_owned(),
+    };
+    write!(buffer, "{}", script).expect("Failed to write to buffer");
+}
+
+/// Get dynamic completion candidates for given words
+///
+/// Words are arguments after program name, the last one is being completed.
+/// Empty result means that shell should use static completions.
+pub fn complete(words: &[String]) -> Vec<String> {
+    let Some((current, previous)) = words.split_last() else {
+        return vec![];
+    };
+    // Arguments after -- are process to start
+    if previous.iter().any(|word| word == "--") {
+        return vec![];
+    }
+    let Some(command) = previous.iter().find(|word| !word.starts_with('-')) else {
+        return vec![];
+    };
+
+    if FILE_COMMANDS.contains(&command.as_str())
+        && previous
+            .last()
+            .is_some_and(|word| word == "--file" || word == "-f")
+    {
+        return dotenv_files(current);
+    }
+    if current.starts_with('-') || !KEY_COMMANDS.contains(&command.as_str()) {
+        return vec![];
+    }
+    // Only first positional argument is variable name, except get that takes many
+    let positional = previous
+        .iter()
+        .skip_while(|word| *word != command)
+        .skip(1)
+        .filter(|word| !word.starts_with('-'))
+        .count();
+    if positional > 0 && command != "get" {
+        return vec![];
+    }
+    let mut names: Vec<String> = env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with(current.as_str()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Get dotenv-looking files and directories that start with given prefix
+fn dotenv_files(prefix: &str) -> Vec<String> {
+    let (dir, name) = match prefix.rfind('/') {
+        Some(index) => (&prefix[..=index], &prefix[index + 1..]),
+        None => ("", prefix),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return vec![];
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.starts_with(name) {
+                return None;
+            }
+            if entry.path().is_dir() {
+                Some(format!("{}{}/", dir, file_name))
+            } else if is_dotenv_file(Path::new(&file_name)) {
+                Some(format!("{}{}", dir, file_name))
+            } else {
+                None
+            }
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Check whether file name looks like dotenv file, e.g. `.env`, `.env.local` or `prod.env`
+pub fn is_dotenv_file(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    name.starts_with(".env") || name.ends_with(".env")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_generate_completions() {
+        for shell in [
+            Shell::Bash,
+            Shell::Zsh,
+            Shell::Fish,
+            Shell::PowerShell,
+            Shell::Elvish,
+        ] {
+            let mut buffer = vec![];
+            generate_completions(shell, &mut buffer);
+            let script = String::from_utf8(buffer).unwrap();
+            assert!(script.contains("envfetch __complete"), "{:?}", shell);
+        }
+    }
+
+    #[test]
+    fn test_complete_variable_names() {
+        unsafe { env::set_var("TEST_COMPLETE_NAME", "value") };
+        let result = complete(&words(&["get", "TEST_COMPLETE_N"]));
+        assert_eq!(result, vec!["TEST_COMPLETE_NAME"]);
+        let result = complete(&words(&["delete", "--global", "TEST_COMPLETE_N"]));
+        assert_eq!(result, vec!["TEST_COMPLETE_NAME"]);
+        let result = complete(&words(&["get", "HOME", "TEST_COMPLETE_N"]));
+        assert_eq!(result, vec!["TEST_COMPLETE_NAME"]);
+        unsafe { env::remove_var("TEST_COMPLETE_NAME") };
+    }
+
+    #[test]
+    fn test_complete_skips_other_positions() {
+        assert!(complete(&words(&["add", "PATH", ""])).is_empty());
+        assert!(complete(&words(&["delete", "PATH", ""])).is_empty());
+        assert!(complete(&words(&["get", "--no"])).is_empty());
+        assert!(complete(&words(&["print", ""])).is_empty());
+        assert!(complete(&words(&["set", "A", "B", "--", ""])).is_empty());
+        assert!(complete(&words(&[""])).is_empty());
+        assert!(complete(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_complete_dotenv_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".env.local"), "").unwrap();
+        fs::write(dir.path().join("prod.env"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+        fs::create_dir(dir.path().join("config")).unwrap();
+        let prefix = format!("{}/", dir.path().display());
+        let result = complete(&words(&["load", "--file", &prefix]));
+        assert_eq!(
+            result,
+            vec![
+                format!("{}.env.local", prefix),
+                format!("{}config/", prefix),
+                format!("{}prod.env", prefix),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_dotenv_file() {
+        assert!(is_dotenv_file(Path::new(".env")));
+        assert!(is_dotenv_file(Path::new(".env.production")));
+        assert!(is_dotenv_file(Path::new("test.env")));
+        assert!(!is_dotenv_file(Path::new("Cargo.toml")));
+    }
+}
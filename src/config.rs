@@ -19,6 +19,16 @@ pub fn get_config_file_path() -> PathBuf {
     get_config_dir().join("envfetch.toml")
 }
 
+/// Get path to journal of global modifications
+pub fn get_history_file_path() -> PathBuf {
+    get_config_dir().join("envfetch_history.toml")
+}
+
+/// Get path to list of dotenv files allowed to be loaded by shell hook
+pub fn get_allowlist_file_path() -> PathBuf {
+    get_config_dir().join("envfetch_allowed.toml")
+}
+
 /// Read config file
 pub fn read_config_from_file(path: PathBuf) -> Result<Config, ConfigParsingError> {
     info!("Reading config at {}", path.display());
@@ -58,6 +68,7 @@ fn default_config() -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::PrintFormat;
     use assert_fs::prelude::*;
     use dirs::config_dir;
 
@@ -65,7 +76,7 @@ mod tests {
 
     impl Write for FailingWriter {
         fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
-            Err(io::Error::new(io::ErrorKind::Other, "Mock write error"))
+            Err(io::Error::other("Mock write error"))
         }
 
         fn flush(&mut self) -> io::Result<()> {
@@ -86,6 +97,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_history_file() {
+        assert_eq!(
+            get_history_file_path(),
+            config_dir()
+                .unwrap_or_default()
+                .join("envfetch_history.toml")
+        );
+    }
+
+    #[test]
+    fn test_get_allowlist_file() {
+        assert_eq!(
+            get_allowlist_file_path(),
+            config_dir()
+                .unwrap_or_default()
+                .join("envfetch_allowed.toml")
+        );
+    }
+
     #[test]
     fn test_default_config() {
         assert_eq!(
@@ -97,7 +128,28 @@ mod tests {
     #[test]
     fn test_read_config_default() {
         let result = read_config(default_config().to_owned()).unwrap();
-        assert_eq!(result, Config { print_format: None })
+        assert_eq!(result, Config::default())
+    }
+
+    #[test]
+    fn test_read_config_print_format_layout() {
+        let result =
+            read_config("print_format = { entry = \"{name}\", separator = \", \" }".to_owned())
+                .unwrap();
+        assert_eq!(
+            result.print_format,
+            Some(PrintFormat::Layout {
+                entry: Some("{name}".to_owned()),
+                header: None,
+                footer: None,
+                separator: Some(", ".to_owned()),
+            })
+        );
+        let result = read_config("print_format = \"{name}\"".to_owned()).unwrap();
+        assert_eq!(
+            result.print_format,
+            Some(PrintFormat::Entry("{name}".to_owned()))
+        );
     }
 
     #[test]
@@ -105,7 +157,7 @@ mod tests {
         let file = assert_fs::NamedTempFile::new("envfetch.toml").unwrap();
         file.write_str(default_config()).unwrap();
         let result = read_config_from_file(file.path().to_path_buf()).unwrap();
-        assert_eq!(result, Config { print_format: None })
+        assert_eq!(result, Config::default())
     }
 
     #[test]
@@ -0,0 +1,229 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use clap::{Arg, Command, CommandFactory};
+
+use crate::models::{Cli, DocsArgs, DocsFormat, ErrorKind};
+
+/// Generate documentation in requested format from command line definitions
+pub fn generate_docs<W: Write>(args: &DocsArgs, buffer: &mut W) -> Result<(), ErrorKind> {
+    let command = cli_command();
+    match (args.format, &args.output) {
+        (DocsFormat::Man, Some(dir)) => {
+            fs::create_dir_all(dir).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+            clap_mangen::generate_to(command, dir)
+                .map_err(|err| ErrorKind::FileError(err.to_string()))?;
+            writeln!(buffer, "Man pages written to {}", dir).expect("Failed to write to buffer");
+        }
+        (DocsFormat::Man, None) => {
+            clap_mangen::Man::new(command)
+                .render(buffer)
+                .map_err(|err| ErrorKind::FileError(err.to_string()))?;
+        }
+        (DocsFormat::Markdown, Some(file)) => {
+            if let Some(parent) = Path::new(file).parent() {
+                fs::create_dir_all(parent).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+            }
+            fs::write(file, render_markdown(command))
+                .map_err(|err| ErrorKind::FileError(err.to_string()))?;
+            writeln!(buffer, "Markdown reference written to {}", file)
+                .expect("Failed to write to buffer");
+        }
+        (DocsFormat::Markdown, None) => {
+            write!(buffer, "{}", render_markdown(command)).expect("Failed to write to buffer")
+        }
+    }
+    Ok(())
+}
+
+/// Get built command definition without implicit help subcommand
+fn cli_command() -> Command {
+    let mut command = Cli::command().disable_help_subcommand(true);
+    command.build();
+    command
+}
+
+/// Render markdown reference for command and all its visible subcommands
+fn render_markdown(mut command: Command) -> String {
+    let mut markdown = format!("# Command-line reference for `{}`\n", command.get_name());
+    render_command(&mut command, &mut markdown);
+    markdown
+}
+
+/// Render section of single command and its subcommands
+fn render_command(command: &mut Command, markdown: &mut String) {
+    let name = command
+        .get_bin_name()
+        .unwrap_or(command.get_name())
+        .to_string();
+    markdown.push_str(&format!("\n## `{}`\n\n", name));
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        markdown.push_str(&format!("{}\n\n", about));
+    }
+    let usage = command.render_usage().to_string();
+    let usage = usage.trim_start_matches("Usage: ");
+    markdown.push_str(&format!("**Usage:** `{}`\n", usage));
+
+    let subcommands: Vec<&Command> = command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .collect();
+    if !subcommands.is_empty() {
+        markdown.push_str("\n**Commands:**\n\n");
+        for subcommand in subcommands {
+            markdown.push_str(&format!("- `{}`", subcommand.get_name()));
+            if let Some(about) = subcommand.get_about() {
+                markdown.push_str(&format!(" — {}", about));
+            }
+            markdown.push('\n');
+        }
+    }
+
+    let arguments: Vec<&Arg> = command
+        .get_positionals()
+        .filter(|arg| !arg.is_hide_set())
+        .collect();
+    if !arguments.is_empty() {
+        markdown.push_str("\n**Arguments:**\n\n");
+        for arg in arguments {
+            render_arg(&format!("<{}>", value_name(arg)), arg, markdown);
+        }
+    }
+
+    let options: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+        .collect();
+    if !options.is_empty() {
+        markdown.push_str("\n**Options:**\n\n");
+        for arg in options {
+            let mut flags = vec![];
+            if let Some(short) = arg.get_short() {
+                flags.push(format!("-{}", short));
+            }
+            if let Some(long) = arg.get_long() {
+                flags.push(format!("--{}", long));
+            }
+            let mut flag = flags.join(", ");
+            if arg.get_action().takes_values() {
+                flag.push_str(&format!(" <{}>", value_name(arg)));
+            }
+            render_arg(&flag, arg, markdown);
+        }
+    }
+
+    for subcommand in command.get_subcommands_mut() {
+        if !subcommand.is_hide_set() {
+            render_command(subcommand, markdown);
+        }
+    }
+}
+
+/// Render list item with argument's help, possible values and defaults
+fn render_arg(flag: &str, arg: &Arg, markdown: &mut String) {
+    markdown.push_str(&format!("- `{}`", flag));
+    if let Some(help) = arg.get_long_help().or(arg.get_help()) {
+        markdown.push_str(&format!(" — {}", help));
+    }
+    let possible_values: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| format!("`{}`", value.get_name()))
+        .collect();
+    if !possible_values.is_empty() {
+        markdown.push_str(&format!(
+            "\n  Possible values: {}",
+            possible_values.join(", ")
+        ));
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| format!("`{}`", value.to_string_lossy()))
+        .collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() {
+        markdown.push_str(&format!("\n  Default: {}", defaults.join(", ")));
+    }
+    markdown.push('\n');
+}
+
+/// Get displayed value name of argument
+fn value_name(arg: &Arg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| arg.get_id().to_string().to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = render_markdown(cli_command());
+        assert!(markdown.starts_with("# Command-line reference for `envfetch`\n"));
+        assert!(markdown.contains("\n## `envfetch get`\n"));
+        assert!(markdown.contains("\n## `envfetch global list`\n"));
+        assert!(markdown.contains("**Usage:** `envfetch get [OPTIONS] <KEY>...`"));
+        assert!(markdown.contains("- `<KEY>` — Environment variable names"));
+        assert!(markdown.contains("- `-n, --count <COUNT>`"));
+        assert!(markdown.contains("Default: `1`"));
+        assert!(markdown.contains("Possible values: `bash`, `zsh`, `fish`"));
+    }
+
+    #[test]
+    fn test_render_markdown_skips_hidden_commands() {
+        let markdown = render_markdown(cli_command());
+        assert!(!markdown.contains("__complete"));
+        assert!(!markdown.contains("hook-env"));
+        assert!(!markdown.contains("## `envfetch help`"));
+    }
+
+    #[test]
+    fn test_generate_man_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("man");
+        let args = DocsArgs {
+            format: DocsFormat::Man,
+            output: Some(output.display().to_string()),
+        };
+        let mut buffer = vec![];
+        generate_docs(&args, &mut buffer).unwrap();
+        assert!(output.join("envfetch.1").exists());
+        assert!(output.join("envfetch-get.1").exists());
+        assert!(output.join("envfetch-global-list.1").exists());
+        assert!(!output.join("envfetch-__complete.1").exists());
+    }
+
+    #[test]
+    fn test_generate_man_page_to_buffer() {
+        let args = DocsArgs {
+            format: DocsFormat::Man,
+            output: None,
+        };
+        let mut buffer = vec![];
+        generate_docs(&args, &mut buffer).unwrap();
+        let page = String::from_utf8(buffer).unwrap();
+        assert!(page.contains(".TH envfetch 1"));
+    }
+
+    #[test]
+    fn test_generate_markdown_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("docs").join("cli.md");
+        let args = DocsArgs {
+            format: DocsFormat::Markdown,
+            output: Some(file.display().to_string()),
+        };
+        let mut buffer = vec![];
+        generate_docs(&args, &mut buffer).unwrap();
+        assert!(
+            fs::read_to_string(file)
+                .unwrap()
+                .contains("## `envfetch load`")
+        );
+    }
+}
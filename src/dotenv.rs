@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::Path;
+
+use crate::models::ErrorKind;
+use crate::variables::PlannedChange;
+
+/// Format variable as dotenv line that is parsed back to the same value
+///
+/// Values are quoted only when needed, dotenv format has no escapes, so
+/// values with both kinds of quotes or line breaks can't be written, and
+/// neither can names with characters other than letters, digits and `_`.
+pub fn format_line(key: &str, value: &str) -> Result<String, ErrorKind> {
+    let valid_key = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(ErrorKind::FileError(format!(
+            "{} isn't valid variable name for dotenv file",
+            key
+        )));
+    }
+    if value.contains(['\n', '\r']) {
+        return Err(ErrorKind::FileError(format!(
+            "value of {} contains line break and can't be written to dotenv file",
+            key
+        )));
+    }
+    let bare = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '#' | '\'' | '"'));
+    if bare {
+        Ok(format!("{}={}", key, value))
+    } else if !value.contains('\'') {
+        Ok(format!("{}='{}'", key, value))
+    } else if !value.contains('"') {
+        Ok(format!("{}=\"{}\"", key, value))
+    } else {
+        Err(ErrorKind::FileError(format!(
+            "value of {} contains both kinds of quotes and can't be written to dotenv file",
+            key
+        )))
+    }
+}
+
+/// Get name of variable defined on dotenv line
+fn line_key(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let line = line
+        .strip_prefix("export")
+        .filter(|rest| rest.starts_with([' ', '\t']))
+        .unwrap_or(line);
+    let (key, _) = line.split_once('=')?;
+    let key = key.trim();
+    (!key.is_empty() && !key.starts_with('#')).then_some(key)
+}
+
+/// Apply changes to content of dotenv file
+///
+/// Changed variables are replaced in place, deleted ones are removed and new
+/// ones are appended, while comments and other lines are kept as is.
+pub fn update_content(content: &str, changes: &[PlannedChange]) -> Result<String, ErrorKind> {
+    let mut lines = vec![];
+    let mut written: Vec<&str> = vec![];
+    for line in content.lines() {
+        let change = line_key(line).and_then(|key| changes.iter().find(|c| c.key == key));
+        match change {
+            None => lines.push(line.to_string()),
+            // Only the first definition is replaced, duplicates are dropped
+            Some(change) if written.contains(&change.key.as_str()) => {}
+            Some(change) => {
+                written.push(&change.key);
+                if let Some(value) = &change.new {
+                    lines.push(format_line(&change.key, value)?);
+                }
+            }
+        }
+    }
+    for change in changes {
+        if let Some(value) = &change.new
+            && !written.contains(&change.key.as_str())
+        {
+            lines.push(format_line(&change.key, value)?);
+        }
+    }
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+/// Replace variables in content of dotenv file with given ones, in given order
+///
+/// Comments and blank lines move together with the variable below them, and
+/// lines of variables whose values didn't change are kept as written.
+pub fn rewrite_content(content: &str, variables: &[(String, String)]) -> Result<String, ErrorKind> {
+    let original = parse(content)?;
+    // Variables in the file with lines above them that aren't definitions
+    let mut blocks: Vec<(&str, Vec<&str>, &str)> = vec![];
+    let mut above = vec![];
+    for line in content.lines() {
+        match line_key(line) {
+            Some(key)
+                if variables.iter().any(|(k, _)| k == key)
+                    && !blocks.iter().any(|(k, ..)| *k == key) =>
+            {
+                blocks.push((key, std::mem::take(&mut above), line));
+            }
+            // Removed variables and duplicates are dropped, comments above them go to the next one
+            Some(_) => {}
+            None => above.push(line),
+        }
+    }
+    let mut lines = vec![];
+    for (key, value) in variables {
+        match blocks.iter().find(|(k, ..)| k == key) {
+            Some((_, above, line)) => {
+                lines.extend(above.iter().map(|line| line.to_string()));
+                if original.get(key) == Some(value) {
+                    lines.push(line.to_string());
+                } else {
+                    lines.push(format_line(key, value)?);
+                }
+            }
+            None => lines.push(format_line(key, value)?),
+        }
+    }
+    lines.extend(above.iter().map(|line| line.to_string()));
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+/// Parse content of dotenv file, empty content has no variables
+fn parse(content: &str) -> Result<BTreeMap<String, String>, ErrorKind> {
+    if content.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    dotenv_parser::parse_dotenv(content).map_err(|err| ErrorKind::ParsingError(err.to_string()))
+}
+
+/// Read content of dotenv file, missing file is empty
+fn read_content(path: &Path) -> Result<String, ErrorKind> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == IoErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(ErrorKind::FileError(err.to_string())),
+    }
+}
+
+/// Read variables from dotenv file in order they are defined in
+pub fn read_variables(path: &Path) -> Result<Vec<(String, String)>, ErrorKind> {
+    let content = read_content(path)?;
+    let mut values = parse(&content)?;
+    let mut variables = vec![];
+    for key in content.lines().filter_map(line_key) {
+        if let Some(value) = values.remove(key) {
+            variables.push((key.to_string(), value));
+        }
+    }
+    variables.extend(values);
+    Ok(variables)
+}
+
+/// Apply changes to dotenv file, creating it if it doesn't exist
+pub fn write_changes(path: &Path, changes: &[PlannedChange]) -> Result<(), ErrorKind> {
+    let content = update_content(&read_content(path)?, changes)?;
+    fs::write(path, content).map_err(|err| ErrorKind::FileError(err.to_string()))
+}
+
+/// Replace variables in dotenv file with given ones, creating it if it doesn't exist
+pub fn write_variables(path: &Path, variables: &[(String, String)]) -> Result<(), ErrorKind> {
+    let content = rewrite_content(&read_content(path)?, variables)?;
+    fs::write(path, content).map_err(|err| ErrorKind::FileError(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(key: &str, new: Option<&str>) -> PlannedChange {
+        PlannedChange {
+            key: key.to_string(),
+            old: None,
+            new: new.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_format_line_roundtrip() {
+        for value in ["plain", "with space", "it's", "", "a#b", "say \"hi\""] {
+            let line = format_line("KEY", value).unwrap();
+            let parsed = dotenv_parser::parse_dotenv(&line).unwrap();
+            assert_eq!(parsed["KEY"], value, "{}", line);
+        }
+        assert!(format_line("KEY", "a'b\"c").is_err());
+        assert!(format_line("KEY", "a\nb").is_err());
+        assert!(format_line("BASH_FUNC_f%%", "() { :; }").is_err());
+        assert!(format_line("1KEY", "value").is_err());
+    }
+
+    #[test]
+    fn test_update_content() {
+        let content =
+            "# database\nDB_HOST=localhost\nexport DB_PORT=5432\nOLD=1\n\nDB_HOST=duplicate\n";
+        let result = update_content(
+            content,
+            &[
+                change("DB_HOST", Some("db.local")),
+                change("DB_PORT", Some("6543")),
+                change("OLD", None),
+                change("NEW", Some("new value")),
+                change("MISSING", None),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            "# database\nDB_HOST=db.local\nDB_PORT=6543\n\nNEW='new value'\n"
+        );
+    }
+
+    fn variables(variables: &[(&str, &str)]) -> Vec<(String, String)> {
+        variables
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_rewrite_content() {
+        let content =
+            "# app\nexport NAME='app'\nPORT=80\n# removed\nOLD=1\n# database\nDB=x\n# end\n";
+        let result = rewrite_content(
+            content,
+            &variables(&[
+                ("DB", "y"),
+                ("NAME", "app"),
+                ("PORT", "8080"),
+                ("NEW", "a b"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            "# removed\n# database\nDB=y\n# app\nexport NAME='app'\nPORT=8080\nNEW='a b'\n# end\n"
+        );
+    }
+
+    #[test]
+    fn test_read_variables_in_file_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        assert!(read_variables(&path).unwrap().is_empty());
+        fs::write(&path, "# comment\nZ=1\nexport A=\"two words\"\nM=3\n").unwrap();
+        assert_eq!(
+            read_variables(&path).unwrap(),
+            variables(&[("Z", "1"), ("A", "two words"), ("M", "3")])
+        );
+        write_variables(&path, &variables(&[("M", "3"), ("Z", "1")])).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "M=3\n# comment\nZ=1\n");
+    }
+
+    #[test]
+    fn test_write_changes_creates_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        write_changes(&path, &[change("A", Some("1"))]).unwrap();
+        write_changes(&path, &[change("B", Some("2"))]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "A=1\nB=2\n");
+    }
+}
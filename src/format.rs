@@ -0,0 +1,571 @@
+use std::io::Write;
+
+use crate::models::ErrorKind;
+use crate::secrets::{MASK, SecretFilter};
+
+/// Values that can be referenced by `{field}` in template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Value,
+    /// Position of variable starting from 1
+    Index,
+    /// Number of printed variables
+    Len,
+}
+
+/// Transformation applied to field with `{field|filter}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    /// Quote for POSIX shell
+    Shell,
+    /// Quote as JSON string
+    Json,
+    /// Cut to given number of characters, marking cut values with `...`
+    Truncate(usize),
+    Lower,
+    Upper,
+}
+
+/// Check made by `{if condition}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    /// Variable is the first one printed
+    First,
+    /// Variable is the last one printed
+    Last,
+    Empty,
+    /// Value is masked as secret
+    Secret,
+}
+
+/// Part of parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field(Field, Vec<Filter>),
+    /// `{if condition}...{else}...{end}`, condition is negated with `not`
+    If {
+        condition: Condition,
+        negated: bool,
+        then: Vec<Segment>,
+        otherwise: Vec<Segment>,
+    },
+}
+
+/// Tag controlling which parts of template are rendered
+enum Tag {
+    If(Condition, bool),
+    Else,
+    End,
+}
+
+/// `{if}` block that is being parsed
+struct Block {
+    condition: Condition,
+    negated: bool,
+    then: Vec<Segment>,
+    /// Set once `{else}` is reached
+    otherwise: Option<Vec<Segment>>,
+}
+
+/// Values for rendering template of single variable
+pub struct Entry<'a> {
+    pub name: &'a str,
+    pub value: &'a str,
+    pub index: usize,
+    pub len: usize,
+    /// Whether value is masked as secret
+    pub secret: bool,
+}
+
+/// Parsed template, e.g. `{name}={value|shell}`
+///
+/// `{{` and `}}` produce literal braces, unless `{{` is followed by placeholder, e.g. `{{name}}`
+/// is name in braces. Braces that don't wrap known field are kept as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parse template, fails on unknown filters and conditions or unbalanced `{if}` blocks
+    pub fn parse(template: &str) -> Result<Self, ErrorKind> {
+        let mut segments = vec![];
+        // Blocks that aren't closed yet, innermost last
+        let mut blocks = vec![];
+        let mut text = String::new();
+        let mut rest = template;
+        while let Some(character) = rest.chars().next() {
+            // Brace before placeholder is kept, so `{{name}}` prints `{NAME}` like before
+            if (rest.starts_with("{{") && !is_placeholder(&rest[1..])) || rest.starts_with("}}") {
+                text.push(character);
+                rest = &rest[2..];
+                continue;
+            }
+            if character == '{'
+                && let Some(end) = rest.find('}')
+            {
+                let placeholder = &rest[1..end];
+                if let Some(tag) = parse_tag(placeholder)? {
+                    push_text(&mut segments, &mut blocks, &mut text);
+                    apply_tag(tag, &mut segments, &mut blocks)?;
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                if let Some((field, filters)) = parse_placeholder(placeholder)? {
+                    push_text(&mut segments, &mut blocks, &mut text);
+                    current(&mut segments, &mut blocks).push(Segment::Field(field, filters));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+            text.push(character);
+            rest = &rest[character.len_utf8()..];
+        }
+        if !blocks.is_empty() {
+            return Err(ErrorKind::TemplateError("{if} without {end}".to_string()));
+        }
+        push_text(&mut segments, &mut blocks, &mut text);
+        Ok(Template { segments })
+    }
+
+    /// Render template for given variable
+    pub fn render(&self, entry: &Entry) -> String {
+        render_segments(&self.segments, entry)
+    }
+}
+
+/// Render parsed segments for given variable
+fn render_segments(segments: &[Segment], entry: &Entry) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.clone(),
+            Segment::Field(field, filters) => {
+                let value = match field {
+                    Field::Name => entry.name.to_string(),
+                    Field::Value => entry.value.to_string(),
+                    Field::Index => entry.index.to_string(),
+                    Field::Len => entry.len.to_string(),
+                };
+                filters
+                    .iter()
+                    .fold(value, |value, filter| apply(filter, value))
+            }
+            Segment::If {
+                condition,
+                negated,
+                then,
+                otherwise,
+            } => {
+                let holds = match condition {
+                    Condition::First => entry.index == 1,
+                    Condition::Last => entry.index == entry.len,
+                    Condition::Empty => entry.value.is_empty(),
+                    Condition::Secret => entry.secret,
+                };
+                if holds != *negated {
+                    render_segments(then, entry)
+                } else {
+                    render_segments(otherwise, entry)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Parse `{if condition}`, `{if not condition}`, `{else}` and `{end}`, returns `None` for other braces
+fn parse_tag(placeholder: &str) -> Result<Option<Tag>, ErrorKind> {
+    let words: Vec<&str> = placeholder.split_whitespace().collect();
+    let (condition, negated) = match words.as_slice() {
+        ["else"] => return Ok(Some(Tag::Else)),
+        ["end"] => return Ok(Some(Tag::End)),
+        ["if", "not", condition] => (*condition, true),
+        ["if", condition] => (*condition, false),
+        ["if", ..] => {
+            return Err(ErrorKind::TemplateError(format!(
+                "expected single condition in {{{}}}",
+                placeholder
+            )));
+        }
+        _ => return Ok(None),
+    };
+    let condition = match condition {
+        "first" => Condition::First,
+        "last" => Condition::Last,
+        "empty" => Condition::Empty,
+        "secret" => Condition::Secret,
+        _ => {
+            return Err(ErrorKind::TemplateError(format!(
+                "unknown condition {:?} in {{{}}}",
+                condition, placeholder
+            )));
+        }
+    };
+    Ok(Some(Tag::If(condition, negated)))
+}
+
+/// Get segments that are currently parsed into, those of innermost open block if any
+fn current<'a>(segments: &'a mut Vec<Segment>, blocks: &'a mut [Block]) -> &'a mut Vec<Segment> {
+    match blocks.last_mut() {
+        Some(Block {
+            otherwise: Some(otherwise),
+            ..
+        }) => otherwise,
+        Some(block) => &mut block.then,
+        None => segments,
+    }
+}
+
+/// Check whether text starts with field or tag, invalid ones count too so their errors are reported
+fn is_placeholder(text: &str) -> bool {
+    text.starts_with('{')
+        && text.find('}').is_some_and(|end| {
+            let placeholder = &text[1..end];
+            !matches!(parse_tag(placeholder), Ok(None))
+                || !matches!(parse_placeholder(placeholder), Ok(None))
+        })
+}
+
+/// Move collected text to current segments
+fn push_text(segments: &mut Vec<Segment>, blocks: &mut [Block], text: &mut String) {
+    if !text.is_empty() {
+        current(segments, blocks).push(Segment::Text(std::mem::take(text)));
+    }
+}
+
+/// Open or close `{if}` block
+fn apply_tag(
+    tag: Tag,
+    segments: &mut Vec<Segment>,
+    blocks: &mut Vec<Block>,
+) -> Result<(), ErrorKind> {
+    match tag {
+        Tag::If(condition, negated) => blocks.push(Block {
+            condition,
+            negated,
+            then: vec![],
+            otherwise: None,
+        }),
+        Tag::Else => match blocks.last_mut() {
+            Some(block) if block.otherwise.is_none() => block.otherwise = Some(vec![]),
+            Some(_) => {
+                return Err(ErrorKind::TemplateError(
+                    "{else} is used twice in one {if} block".to_string(),
+                ));
+            }
+            None => return Err(ErrorKind::TemplateError("{else} without {if}".to_string())),
+        },
+        Tag::End => {
+            let block = blocks
+                .pop()
+                .ok_or_else(|| ErrorKind::TemplateError("{end} without {if}".to_string()))?;
+            current(segments, blocks).push(Segment::If {
+                condition: block.condition,
+                negated: block.negated,
+                then: block.then,
+                otherwise: block.otherwise.unwrap_or_default(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Parse contents of braces, returns `None` if they don't reference known field
+fn parse_placeholder(placeholder: &str) -> Result<Option<(Field, Vec<Filter>)>, ErrorKind> {
+    let mut parts = placeholder.split('|');
+    let field = match parts.next().unwrap_or_default().trim() {
+        "name" => Field::Name,
+        "value" => Field::Value,
+        "index" => Field::Index,
+        "len" => Field::Len,
+        _ => return Ok(None),
+    };
+    let filters = parts
+        .map(|filter| {
+            let (filter, argument) = match filter.trim().split_once(':') {
+                Some((filter, argument)) => (filter.trim(), Some(argument.trim())),
+                None => (filter.trim(), None),
+            };
+            match (filter, argument) {
+                ("shell", None) => Ok(Filter::Shell),
+                ("json", None) => Ok(Filter::Json),
+                ("lower", None) => Ok(Filter::Lower),
+                ("upper", None) => Ok(Filter::Upper),
+                ("truncate", Some(length)) => length.parse().map(Filter::Truncate).map_err(|_| {
+                    ErrorKind::TemplateError(format!(
+                        "truncate expects number of characters, got {:?} in {{{}}}",
+                        length, placeholder
+                    ))
+                }),
+                _ => Err(ErrorKind::TemplateError(format!(
+                    "unknown filter {:?} in {{{}}}",
+                    filter, placeholder
+                ))),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Some((field, filters)))
+}
+
+/// Apply filter to rendered field
+fn apply(filter: &Filter, value: String) -> String {
+    match filter {
+        Filter::Shell => format!("'{}'", value.replace('\'', "'\\''")),
+        Filter::Json => serde_json::to_string(&value).expect("Failed to serialize string"),
+        Filter::Truncate(length) => {
+            if value.chars().count() > *length {
+                format!("{}...", value.chars().take(*length).collect::<String>())
+            } else {
+                value
+            }
+        }
+        Filter::Lower => value.to_lowercase(),
+        Filter::Upper => value.to_uppercase(),
+    }
+}
+
+/// Layout of printed list of variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    /// Template of every variable
+    pub entry: String,
+    /// Printed before variables, may reference `{len}`
+    pub header: Option<String>,
+    /// Printed after variables, may reference `{len}`
+    pub footer: Option<String>,
+    /// Printed between variables
+    pub separator: String,
+}
+
+impl Layout {
+    /// Layout with given entry template and entries on separate lines
+    pub fn new(entry: &str) -> Self {
+        Layout {
+            entry: entry.to_string(),
+            header: None,
+            footer: None,
+            separator: "\n".to_string(),
+        }
+    }
+}
+
+/// Write variables using given layout, masking secrets if filter is given
+pub fn write_variables<W: Write>(
+    variables: &[(String, String)],
+    layout: &Layout,
+    secrets: Option<&SecretFilter>,
+    mut buffer: W,
+) -> Result<(), ErrorKind> {
+    let template = Template::parse(&layout.entry)?;
+    let header = layout.header.as_deref().map(Template::parse).transpose()?;
+    let footer = layout.footer.as_deref().map(Template::parse).transpose()?;
+    let len = variables.len();
+    // Header and footer only have {len}, other fields are empty there
+    let summary = Entry {
+        name: "",
+        value: "",
+        index: 0,
+        len,
+        secret: false,
+    };
+    if let Some(header) = header {
+        writeln!(buffer, "{}", header.render(&summary)).expect("Failed to write to buffer");
+    }
+    let entries: Vec<String> = variables
+        .iter()
+        .enumerate()
+        .map(|(index, (name, value))| {
+            let secret = secrets.is_some_and(|secrets| secrets.is_secret(name, value));
+            template.render(&Entry {
+                name,
+                value: if secret { MASK } else { value },
+                index: index + 1,
+                len,
+                secret,
+            })
+        })
+        .collect();
+    if !entries.is_empty() {
+        writeln!(buffer, "{}", entries.join(&layout.separator)).expect("Failed to write to buffer");
+    }
+    if let Some(footer) = footer {
+        writeln!(buffer, "{}", footer.render(&summary)).expect("Failed to write to buffer");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, name: &str, value: &str) -> String {
+        Template::parse(template).unwrap().render(&Entry {
+            name,
+            value,
+            index: 2,
+            len: 5,
+            secret: false,
+        })
+    }
+
+    #[test]
+    fn test_render_fields() {
+        assert_eq!(
+            render("{index}/{len} {name} = \"{value}\"", "HOME", "/root"),
+            "2/5 HOME = \"/root\""
+        );
+    }
+
+    #[test]
+    fn test_value_is_not_substituted_again() {
+        assert_eq!(render("{name}={value}", "A", "{name}"), "A={name}");
+    }
+
+    #[test]
+    fn test_render_filters() {
+        assert_eq!(render("{value|shell}", "A", "it's"), "'it'\\''s'");
+        assert_eq!(
+            render("{value|json}", "A", "say \"hi\""),
+            "\"say \\\"hi\\\"\""
+        );
+        assert_eq!(render("{value|truncate:3}", "A", "abcdef"), "abc...");
+        assert_eq!(render("{value|truncate:10}", "A", "abcdef"), "abcdef");
+        assert_eq!(render("{name|lower}", "HOME", ""), "home");
+        assert_eq!(render("{ name | lower | upper }", "Home", ""), "HOME");
+        assert_eq!(render("{value|truncate:2|json}", "A", "abc"), "\"ab...\"");
+    }
+
+    #[test]
+    fn test_literal_braces() {
+        assert_eq!(
+            render("{{\"{name}\": {value|json}}}", "A", "1"),
+            "{\"A\": \"1\"}"
+        );
+        assert_eq!(render("{unknown} {", "A", "1"), "{unknown} {");
+    }
+
+    #[test]
+    fn test_braces_around_placeholder() {
+        // Formats written before templates had escaping keep their output
+        assert_eq!(render("{{name}}={{value}}", "A", "1"), "{A}={1}");
+        assert_eq!(render("${{name}}", "A", "1"), "${A}");
+        assert_eq!(render("{{value|json}}", "A", "1"), "{\"1\"}");
+        assert!(Template::parse("{{value|reverse}}").is_err());
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        assert!(matches!(
+            Template::parse("{value|reverse}"),
+            Err(ErrorKind::TemplateError(message)) if message == "unknown filter \"reverse\" in {value|reverse}"
+        ));
+        assert!(matches!(
+            Template::parse("{value|truncate:many}"),
+            Err(ErrorKind::TemplateError(_))
+        ));
+        assert!(Template::parse("{value|truncate}").is_err());
+    }
+
+    #[test]
+    fn test_render_conditionals() {
+        let template = "{if first}[{end}{name}{if not last},{else}]{end}";
+        assert_eq!(render(template, "A", "1"), "A,");
+        let entry = |index| Entry {
+            name: "A",
+            value: "",
+            index,
+            len: 2,
+            secret: false,
+        };
+        let template = Template::parse(template).unwrap();
+        assert_eq!(template.render(&entry(1)), "[A,");
+        assert_eq!(template.render(&entry(2)), "A]");
+        assert_eq!(
+            render("{name}{if empty} (empty){end}", "A", ""),
+            "A (empty)"
+        );
+        assert_eq!(
+            render("{if not empty}{if first}x{else}{value}{end}{end}", "A", "1"),
+            "1"
+        );
+    }
+
+    #[test]
+    fn test_invalid_conditionals() {
+        for template in [
+            "{if first}a",
+            "a{end}",
+            "{else}",
+            "{if last}a{else}b{else}c{end}",
+            "{if public}a{end}",
+            "{if not}a{end}",
+        ] {
+            assert!(
+                matches!(Template::parse(template), Err(ErrorKind::TemplateError(_))),
+                "{}",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_variables_secret_condition() {
+        let variables = vec![
+            ("API_TOKEN".to_string(), "abc".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+        let layout = Layout::new("{name}={if secret}<hidden {value}>{else}{value}{end}");
+        let secrets = SecretFilter::new(vec![], vec![]);
+        let mut buffer = vec![];
+        write_variables(&variables, &layout, Some(&secrets), &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            format!("API_TOKEN=<hidden {}>\nHOME=/root\n", MASK)
+        );
+        let mut buffer = vec![];
+        write_variables(&variables, &layout, None, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "API_TOKEN=abc\nHOME=/root\n"
+        );
+    }
+
+    #[test]
+    fn test_write_variables_layout() {
+        let variables = vec![
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "2".to_string()),
+        ];
+        let layout = Layout {
+            entry: "{name|json}: {value|json}".to_string(),
+            header: Some("{{ // {len} variables".to_string()),
+            footer: Some("}}".to_string()),
+            separator: ",\n".to_string(),
+        };
+        let mut buffer = vec![];
+        write_variables(&variables, &layout, None, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{ // 2 variables\n\"A\": \"1\",\n\"B\": \"2\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_write_variables_default_separator() {
+        let variables = vec![
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "2".to_string()),
+        ];
+        let mut buffer = vec![];
+        write_variables(
+            &variables,
+            &Layout::new("{name}={value}"),
+            None,
+            &mut buffer,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "A=1\nB=2\n");
+    }
+}
@@ -0,0 +1,342 @@
+use std::env;
+use std::fmt::Display;
+#[cfg(unix)]
+use std::{fs, path::PathBuf};
+
+use crate::models::ErrorKind;
+
+/// Place where globally set variables are persisted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobalStore {
+    /// Shell startup file with `export` lines
+    #[cfg(unix)]
+    ShellFile(PathBuf),
+    /// `Environment` key of current user's registry hive
+    #[cfg(windows)]
+    Registry,
+}
+
+impl Display for GlobalStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(unix)]
+            GlobalStore::ShellFile(path) => write!(f, "{}", path.display()),
+            #[cfg(windows)]
+            GlobalStore::Registry => write!(f, "HKEY_CURRENT_USER\\Environment"),
+        }
+    }
+}
+
+/// State of persisted variable compared to current session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobalStatus {
+    /// Current session has the same value
+    Active,
+    /// Variable isn't visible in current session yet
+    NotLoaded,
+    /// Current session has different value
+    Shadowed(String),
+    /// Later entry in the same store sets this variable again
+    Overridden,
+}
+
+/// Variable read from global store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalVariable {
+    pub name: String,
+    pub value: String,
+    /// Location of entry in the store, e.g. file and line
+    pub source: String,
+    pub status: GlobalStatus,
+}
+
+impl Display for GlobalVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {:?} ({})", self.name, self.value, self.source)?;
+        match &self.status {
+            GlobalStatus::Active => Ok(()),
+            GlobalStatus::NotLoaded => write!(f, " [not loaded in current session]"),
+            GlobalStatus::Shadowed(current) => {
+                write!(f, " [shadowed in current session by {:?}]", current)
+            }
+            GlobalStatus::Overridden => write!(f, " [overridden by later entry]"),
+        }
+    }
+}
+
+/// Get store used for global operations on this system
+#[cfg(unix)]
+pub fn get_store() -> Result<GlobalStore, ErrorKind> {
+    let home = env::var("HOME")
+        .map_err(|_| ErrorKind::CannotReadGlobalVariables("HOME is not set".to_string()))?;
+    let shell = env::var("SHELL")
+        .map_err(|_| ErrorKind::CannotReadGlobalVariables("SHELL is not set".to_string()))?;
+    shell_file(&home, &shell)
+        .map(GlobalStore::ShellFile)
+        .ok_or_else(|| {
+            ErrorKind::CannotReadGlobalVariables(format!("unsupported shell: {}", shell))
+        })
+}
+
+/// Get store used for global operations on this system
+#[cfg(windows)]
+pub fn get_store() -> Result<GlobalStore, ErrorKind> {
+    Ok(GlobalStore::Registry)
+}
+
+/// Get path to startup file that is used for given shell
+#[cfg(unix)]
+fn shell_file(home: &str, shell: &str) -> Option<PathBuf> {
+    let file = match shell {
+        "/usr/bin/zsh" | "/bin/zsh" => ".zshenv",
+        "/bin/bash" => ".bashrc",
+        _ => return None,
+    };
+    Some(PathBuf::from(home).join(file))
+}
+
+/// Read all variables from given store
+pub fn read_variables(store: &GlobalStore) -> Result<Vec<GlobalVariable>, ErrorKind> {
+    let entries = read_entries(store)?;
+    Ok(resolve_status(entries, |name| env::var(name).ok()))
+}
+
+/// Get variable with given name from store
+pub fn get_variable(store: &GlobalStore, name: &str) -> Result<GlobalVariable, ErrorKind> {
+    read_variables(store)?
+        .into_iter()
+        .rev()
+        .find(|variable| variable.name == name)
+        .ok_or_else(|| ErrorKind::CannotFindVariable(name.to_string(), true))
+}
+
+/// Remove every entry of variable with given name from store
+pub fn remove_variable(store: &GlobalStore, name: &str) -> Result<(), ErrorKind> {
+    match store {
+        #[cfg(unix)]
+        GlobalStore::ShellFile(path) => {
+            if !path.exists() {
+                return Ok(());
+            }
+            let content = fs::read_to_string(path)
+                .map_err(|err| ErrorKind::CannotDeleteVariableGlobally(err.to_string()))?;
+            fs::write(path, remove_exports(&content, name))
+                .map_err(|err| ErrorKind::CannotDeleteVariableGlobally(err.to_string()))
+        }
+        #[cfg(windows)]
+        GlobalStore::Registry => globalenv::unset_var(name)
+            .map_err(|err| ErrorKind::CannotDeleteVariableGlobally(err.to_string())),
+    }
+}
+
+/// Read name, value and source of every entry in store
+fn read_entries(store: &GlobalStore) -> Result<Vec<(String, String, String)>, ErrorKind> {
+    match store {
+        #[cfg(unix)]
+        GlobalStore::ShellFile(path) => {
+            if !path.exists() {
+                return Ok(vec![]);
+            }
+            let content = fs::read_to_string(path)
+                .map_err(|err| ErrorKind::CannotReadGlobalVariables(err.to_string()))?;
+            Ok(parse_exports(&content)
+                .into_iter()
+                .map(|(line, name, value)| (name, value, format!("{}:{}", path.display(), line)))
+                .collect())
+        }
+        #[cfg(windows)]
+        GlobalStore::Registry => {
+            use winreg::{RegKey, enums::HKEY_CURRENT_USER, types::FromRegValue};
+
+            let key = RegKey::predef(HKEY_CURRENT_USER)
+                .open_subkey("Environment")
+                .map_err(|err| ErrorKind::CannotReadGlobalVariables(err.to_string()))?;
+            Ok(key
+                .enum_values()
+                .filter_map(Result::ok)
+                .filter_map(|(name, value)| {
+                    String::from_reg_value(&value)
+                        .ok()
+                        .map(|value| (name, value, store.to_string()))
+                })
+                .collect())
+        }
+    }
+}
+
+/// Parse `export NAME=VALUE` lines, returns line number, name and value of each
+#[cfg(unix)]
+fn parse_exports(content: &str) -> Vec<(usize, String, String)> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (name, value) = line.trim_start().strip_prefix("export ")?.split_once('=')?;
+            if name.is_empty() {
+                return None;
+            }
+            Some((index + 1, name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Remove `export NAME=` lines of given variable, keeping other lines intact
+#[cfg(unix)]
+fn remove_exports(content: &str, name: &str) -> String {
+    let removed: Vec<usize> = parse_exports(content)
+        .into_iter()
+        .filter(|(_, export, _)| export == name)
+        .map(|(line, _, _)| line)
+        .collect();
+    content
+        .lines()
+        .enumerate()
+        .filter(|(index, _)| !removed.contains(&(index + 1)))
+        .map(|(_, line)| format!("{}\n", line))
+        .collect()
+}
+
+/// Compare entries with current session using given lookup function
+fn resolve_status<F>(entries: Vec<(String, String, String)>, lookup: F) -> Vec<GlobalVariable>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let names: Vec<String> = entries.iter().map(|(name, _, _)| name.clone()).collect();
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, value, source))| {
+            let status = if names[index + 1..].contains(&name) {
+                GlobalStatus::Overridden
+            } else {
+                match lookup(&name) {
+                    Some(current) if current == value => GlobalStatus::Active,
+                    Some(current) => GlobalStatus::Shadowed(current),
+                    None => GlobalStatus::NotLoaded,
+                }
+            };
+            GlobalVariable {
+                name,
+                value,
+                source,
+                status,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, value: &str) -> (String, String, String) {
+        (name.to_string(), value.to_string(), "test".to_string())
+    }
+
+    #[test]
+    fn test_resolve_status() {
+        let lookup = |name: &str| match name {
+            "ACTIVE" => Some("1".to_string()),
+            "SHADOWED" => Some("current".to_string()),
+            _ => None,
+        };
+        let result = resolve_status(
+            vec![
+                entry("ACTIVE", "1"),
+                entry("SHADOWED", "persisted"),
+                entry("MISSING", "1"),
+            ],
+            lookup,
+        );
+        assert_eq!(result[0].status, GlobalStatus::Active);
+        assert_eq!(
+            result[1].status,
+            GlobalStatus::Shadowed("current".to_string())
+        );
+        assert_eq!(result[2].status, GlobalStatus::NotLoaded);
+    }
+
+    #[test]
+    fn test_resolve_status_overridden() {
+        let result = resolve_status(vec![entry("VAR", "old"), entry("VAR", "new")], |_| {
+            Some("new".to_string())
+        });
+        assert_eq!(result[0].status, GlobalStatus::Overridden);
+        assert_eq!(result[1].status, GlobalStatus::Active);
+    }
+
+    #[test]
+    fn test_global_variable_display() {
+        let variable = GlobalVariable {
+            name: "VAR".to_string(),
+            value: "value".to_string(),
+            source: "/home/user/.bashrc:3".to_string(),
+            status: GlobalStatus::NotLoaded,
+        };
+        assert_eq!(
+            variable.to_string(),
+            "VAR = \"value\" (/home/user/.bashrc:3) [not loaded in current session]"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_file() {
+        assert_eq!(
+            shell_file("/home/user", "/bin/bash"),
+            Some(PathBuf::from("/home/user/.bashrc"))
+        );
+        assert_eq!(
+            shell_file("/home/user", "/usr/bin/zsh"),
+            Some(PathBuf::from("/home/user/.zshenv"))
+        );
+        assert_eq!(shell_file("/home/user", "/usr/bin/fish"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_exports() {
+        let content = "# comment\nexport A=1\nalias ll='ls -l'\n  export B=x=y\nexport =broken\n";
+        assert_eq!(
+            parse_exports(content),
+            vec![
+                (2, "A".to_string(), "1".to_string()),
+                (4, "B".to_string(), "x=y".to_string()),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_exports() {
+        let content = "export PATH=/bin\nexport MANPATH=/man\nalias path='echo $PATH'\n  export PATH=/usr/bin\nexport PATH_EXTRA=1\n";
+        assert_eq!(
+            remove_exports(content, "PATH"),
+            "export MANPATH=/man\nalias path='echo $PATH'\nexport PATH_EXTRA=1\n"
+        );
+        assert_eq!(remove_exports(content, "MISSING"), content);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_variables_from_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), "export TEST_GLOBAL_READ=persisted\n").unwrap();
+        let store = GlobalStore::ShellFile(file.path().to_path_buf());
+        let variable = get_variable(&store, "TEST_GLOBAL_READ").unwrap();
+        assert_eq!(variable.value, "persisted");
+        assert_eq!(variable.status, GlobalStatus::NotLoaded);
+        assert_eq!(variable.source, format!("{}:1", file.path().display()));
+        assert!(matches!(
+            get_variable(&store, "TEST_GLOBAL_MISSING"),
+            Err(ErrorKind::CannotFindVariable(_, true))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_variables_missing_file() {
+        let store = GlobalStore::ShellFile(PathBuf::from("/non/existent/.bashrc"));
+        assert!(read_variables(&store).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::ErrorKind;
+
+/// Guards journal file from concurrent writes, e.g. from parallel load
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Global modification recorded in journal
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Unix timestamp of modification
+    pub timestamp: u64,
+    /// Variable name
+    pub key: String,
+    /// Value before modification, missing if variable wasn't set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+    /// Value after modification, missing if variable was deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Journal {
+    #[serde(default)]
+    changes: Vec<Change>,
+}
+
+/// Record global change of variable after it was made in journal at given path
+pub fn record_change(
+    path: &Path,
+    key: &str,
+    previous: Option<String>,
+    value: Option<&str>,
+) -> Result<(), ErrorKind> {
+    let change = Change {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+        key: key.to_string(),
+        previous,
+        value: value.map(str::to_string),
+    };
+    let _lock = JOURNAL_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    push_change(path, change)
+}
+
+/// Get all changes recorded in journal at given path, oldest first
+pub fn get_changes(path: &Path) -> Result<Vec<Change>, ErrorKind> {
+    read_journal(path).map(|journal| journal.changes)
+}
+
+/// Remove latest change from journal at given path and return it
+pub fn pop_change(path: &Path) -> Result<Option<Change>, ErrorKind> {
+    let _lock = JOURNAL_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    pop_change_from(path)
+}
+
+/// Append change to journal at given path
+fn push_change(path: &Path, change: Change) -> Result<(), ErrorKind> {
+    let mut journal = read_journal(path)?;
+    journal.changes.push(change);
+    write_journal(path, &journal)
+}
+
+/// Remove latest change from journal at given path
+fn pop_change_from(path: &Path) -> Result<Option<Change>, ErrorKind> {
+    let mut journal = read_journal(path)?;
+    let change = journal.changes.pop();
+    if change.is_some() {
+        write_journal(path, &journal)?;
+    }
+    Ok(change)
+}
+
+/// Read journal, missing file is treated as empty journal
+fn read_journal(path: &Path) -> Result<Journal, ErrorKind> {
+    if !path.exists() {
+        return Ok(Journal::default());
+    }
+    let content =
+        fs::read_to_string(path).map_err(|err| ErrorKind::HistoryError(err.to_string()))?;
+    toml::from_str(&content).map_err(|err| ErrorKind::HistoryError(err.to_string()))
+}
+
+/// Write journal, creating parent directory if needed
+fn write_journal(path: &Path, journal: &Journal) -> Result<(), ErrorKind> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| ErrorKind::HistoryError(err.to_string()))?;
+    }
+    let content =
+        toml::to_string(journal).map_err(|err| ErrorKind::HistoryError(err.to_string()))?;
+    fs::write(path, content).map_err(|err| ErrorKind::HistoryError(err.to_string()))
+}
+
+/// Format time passed since given timestamp, e.g. `5m ago`
+pub fn format_age(timestamp: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(timestamp);
+    match seconds {
+        0..60 => format!("{}s ago", seconds),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(key: &str, previous: Option<&str>, value: Option<&str>) -> Change {
+        Change {
+            timestamp: 0,
+            key: key.to_string(),
+            previous: previous.map(str::to_string),
+            value: value.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_push_and_pop_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("history.toml");
+        push_change(&path, change("FIRST", None, Some("1"))).unwrap();
+        push_change(&path, change("SECOND", Some("old"), None)).unwrap();
+
+        let journal = read_journal(&path).unwrap();
+        assert_eq!(journal.changes.len(), 2);
+        assert_eq!(
+            pop_change_from(&path).unwrap(),
+            Some(change("SECOND", Some("old"), None))
+        );
+        assert_eq!(
+            pop_change_from(&path).unwrap(),
+            Some(change("FIRST", None, Some("1")))
+        );
+        assert_eq!(pop_change_from(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn test_record_and_pop_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.toml");
+        record_change(&path, "KEY", Some("old".to_string()), Some("new")).unwrap();
+        let changes = get_changes(&path).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].key, "KEY");
+        assert_eq!(changes[0].previous.as_deref(), Some("old"));
+        assert_eq!(changes[0].value.as_deref(), Some("new"));
+        assert_eq!(pop_change(&path).unwrap(), Some(changes[0].clone()));
+        assert!(get_changes(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_journal_missing_file() {
+        let journal = read_journal(Path::new("/non/existent/history.toml")).unwrap();
+        assert!(journal.changes.is_empty());
+    }
+
+    #[test]
+    fn test_read_journal_invalid_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), "changes = 5").unwrap();
+        assert!(matches!(
+            read_journal(file.path()),
+            Err(ErrorKind::HistoryError(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(100, 130), "30s ago");
+        assert_eq!(format_age(0, 300), "5m ago");
+        assert_eq!(format_age(0, 7200), "2h ago");
+        assert_eq!(format_age(0, 172800), "2d ago");
+        assert_eq!(format_age(10, 0), "0s ago");
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::get_allowlist_file_path;
+use crate::models::{ErrorKind, HookShell, ValidationProfile};
+use crate::utils::validate_var_name;
+use crate::variables::PlannedChange;
+
+/// Name of variable where hook keeps track of loaded file
+pub const STATE_VARIABLE: &str = "ENVFETCH_DIR_STATE";
+
+/// Name of file that hook looks for
+const DOTENV_FILE: &str = ".env";
+
+/// Dotenv file loaded by hook and values it replaced
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct HookState {
+    pub file: String,
+    pub hash: String,
+    /// Values that variables had before file was loaded
+    #[serde(default)]
+    pub previous: BTreeMap<String, String>,
+    /// Variables that weren't set before file was loaded
+    #[serde(default)]
+    pub unset: Vec<String>,
+}
+
+impl HookState {
+    /// Encode state into string safe to use as variable value in any shell
+    pub fn encode(&self) -> String {
+        toml::to_string(self)
+            .unwrap_or_default()
+            .bytes()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Decode state produced by [`HookState::encode`]
+    pub fn decode(encoded: &str) -> Option<Self> {
+        if !encoded.len().is_multiple_of(2) {
+            return None;
+        }
+        let bytes = (0..encoded.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(encoded.get(index..index + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        toml::from_str(&String::from_utf8(bytes).ok()?).ok()
+    }
+}
+
+/// List of files allowed to be loaded by hook with hashes of their content
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Allowlist {
+    #[serde(default)]
+    allowed: BTreeMap<String, String>,
+}
+
+/// Find nearest dotenv file, walking up from given directory
+pub fn find_dotenv(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(DOTENV_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Get hex-encoded SHA-256 hash of content
+pub fn hash_content(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Allow hook to load given file with its current content
+pub fn allow_file(file: &Path) -> Result<PathBuf, ErrorKind> {
+    let file = fs::canonicalize(file).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+    let content = fs::read_to_string(&file).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+    let path = get_allowlist_file_path();
+    let mut allowlist = read_allowlist(&path)?;
+    allowlist
+        .allowed
+        .insert(file.display().to_string(), hash_content(&content));
+    write_allowlist(&path, &allowlist)?;
+    Ok(file)
+}
+
+/// Check whether file with given content is allowed
+fn is_allowed(allowlist: &Allowlist, file: &str, hash: &str) -> bool {
+    allowlist
+        .allowed
+        .get(file)
+        .is_some_and(|allowed| allowed == hash)
+}
+
+/// Read allowlist, missing file is treated as empty allowlist
+fn read_allowlist(path: &Path) -> Result<Allowlist, ErrorKind> {
+    if !path.exists() {
+        return Ok(Allowlist::default());
+    }
+    let content = fs::read_to_string(path).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+    toml::from_str(&content).map_err(|err| ErrorKind::ParsingError(err.to_string()))
+}
+
+/// Write allowlist, creating parent directory if needed
+fn write_allowlist(path: &Path, allowlist: &Allowlist) -> Result<(), ErrorKind> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| ErrorKind::FileError(err.to_string()))?;
+    }
+    let content =
+        toml::to_string(allowlist).map_err(|err| ErrorKind::ParsingError(err.to_string()))?;
+    fs::write(path, content).map_err(|err| ErrorKind::FileError(err.to_string()))
+}
+
+/// Get script that installs hook into given shell
+pub fn render_hook(shell: HookShell, executable: &str) -> String {
+    match shell {
+        HookShell::Bash => format!(
+            r#"_envfetch_hook() {{
+  local previous_exit_status=$?;
+  eval "$("{executable}" hook-env bash)";
+  return $previous_exit_status;
+}};
+if [[ ";${{PROMPT_COMMAND[*]:-}};" != *";_envfetch_hook;"* ]]; then
+  PROMPT_COMMAND="_envfetch_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}";
+fi
+"#
+        ),
+        HookShell::Zsh => format!(
+            r#"_envfetch_hook() {{
+  eval "$("{executable}" hook-env zsh)";
+}}
+typeset -ag precmd_functions;
+if (( ! ${{precmd_functions[(I)_envfetch_hook]}} )); then
+  precmd_functions=(_envfetch_hook $precmd_functions);
+fi
+typeset -ag chpwd_functions;
+if (( ! ${{chpwd_functions[(I)_envfetch_hook]}} )); then
+  chpwd_functions=(_envfetch_hook $chpwd_functions);
+fi
+"#
+        ),
+        HookShell::Fish => format!(
+            r#"function __envfetch_hook --on-event fish_prompt --on-variable PWD
+    "{executable}" hook-env fish | source
+end
+"#
+        ),
+    }
+}
+
+/// Get statements that bring environment in sync with nearest allowed dotenv file
+///
+/// `lookup` returns current value of variable, `state` is value of [`STATE_VARIABLE`].
+pub fn hook_env<F>(shell: HookShell, dir: &Path, state: Option<&str>, lookup: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let allowlist = read_allowlist(&get_allowlist_file_path()).unwrap_or_else(|err| {
+        warn!("{}", err);
+        Allowlist::default()
+    });
+    sync_statements(shell, dir, state, &allowlist, lookup)
+}
+
+/// Build statements for hook using given allowlist
+fn sync_statements<F>(
+    shell: HookShell,
+    dir: &Path,
+    state: Option<&str>,
+    allowlist: &Allowlist,
+    lookup: F,
+) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let current = state.and_then(HookState::decode);
+    let nearest = find_dotenv(dir).and_then(|file| {
+        let file = fs::canonicalize(file).ok()?;
+        let content = fs::read_to_string(&file).ok()?;
+        Some((file.display().to_string(), content))
+    });
+
+    // Nothing to do if the same file with the same content is loaded
+    if let (Some(current), Some((file, content))) = (&current, &nearest)
+        && current.file == *file
+        && current.hash == hash_content(content)
+    {
+        return String::new();
+    }
+
+    let mut statements = String::new();
+    // Restore variables that were replaced by previously loaded file
+    // Keys end up in evaluated script, so names that aren't valid in shells are never restored
+    let current = current.unwrap_or_default();
+    for (key, value) in &current.previous {
+        if is_shell_name(key) {
+            statements.push_str(&statement(shell, key, Some(value)));
+        }
+    }
+    for key in current.unset.iter().filter(|key| is_shell_name(key)) {
+        statements.push_str(&statement(shell, key, None));
+    }
+    let lookup = |key: &str| {
+        if current.unset.iter().any(|unset| unset == key) {
+            return None;
+        }
+        current.previous.get(key).cloned().or_else(|| lookup(key))
+    };
+
+    let mut state = None;
+    if let Some((file, content)) = nearest {
+        let hash = hash_content(&content);
+        if !is_allowed(allowlist, &file, &hash) {
+            warn!("{} is blocked, run `envfetch allow` to load it", file);
+        } else {
+            match dotenv_parser::parse_dotenv(&content) {
+                Ok(variables) => {
+                    let mut loaded = HookState {
+                        file,
+                        hash,
+                        ..Default::default()
+                    };
+                    for (key, value) in variables {
+                        if let Err(err) = validate_var_name(&key, ValidationProfile::Posix) {
+                            warn!("skipping {} from {}: {}", key, loaded.file, err);
+                            continue;
+                        }
+                        match lookup(&key) {
+                            Some(previous) => {
+                                loaded.previous.insert(key.clone(), previous);
+                            }
+                            None => loaded.unset.push(key.clone()),
+                        }
+                        statements.push_str(&statement(shell, &key, Some(&value)));
+                    }
+                    state = Some(loaded);
+                }
+                Err(err) => warn!("failed to parse {}: {}", file, err),
+            }
+        }
+    }
+    let encoded = state.map(|state| state.encode());
+    statements.push_str(&statement(shell, STATE_VARIABLE, encoded.as_deref()));
+    statements
+}
+
+/// Check whether name can be used in export statement without being interpreted by shell
+fn is_shell_name(key: &str) -> bool {
+    validate_var_name(key, ValidationProfile::Posix).is_ok()
+}
+
+/// Get statements exporting given changes, skipping names shell would interpret
+pub fn export_statements(shell: HookShell, changes: &[PlannedChange]) -> String {
+    let mut statements = String::new();
+    for change in changes {
+        if let Err(err) = validate_var_name(&change.key, ValidationProfile::Posix) {
+            warn!("skipping {}: {}", change.key, err);
+            continue;
+        }
+        statements.push_str(&statement(shell, &change.key, change.new.as_deref()));
+    }
+    statements
+}
+
+/// Get statement that sets or unsets variable in given shell
+pub fn statement(shell: HookShell, key: &str, value: Option<&str>) -> String {
+    match (shell, value) {
+        (HookShell::Bash | HookShell::Zsh, Some(value)) => {
+            format!("export {}='{}';\n", key, value.replace('\'', "'\\''"))
+        }
+        (HookShell::Bash | HookShell::Zsh, None) => format!("unset {};\n", key),
+        (HookShell::Fish, Some(value)) => format!(
+            "set -gx {} '{}';\n",
+            key,
+            value.replace('\\', "\\\\").replace('\'', "\\'")
+        ),
+        (HookShell::Fish, None) => format!("set -e {};\n", key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist_for(file: &Path) -> Allowlist {
+        let file = fs::canonicalize(file).unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        Allowlist {
+            allowed: BTreeMap::from([(file.display().to_string(), hash_content(&content))]),
+        }
+    }
+
+    #[test]
+    fn test_hook_state_roundtrip() {
+        let state = HookState {
+            file: "/project/.env".to_string(),
+            hash: "abc".to_string(),
+            previous: BTreeMap::from([("OLD".to_string(), "it's \"quoted\"".to_string())]),
+            unset: vec!["NEW".to_string()],
+        };
+        let encoded = state.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(HookState::decode(&encoded), Some(state));
+        assert_eq!(HookState::decode("zz"), None);
+        assert_eq!(HookState::decode("abc"), None);
+    }
+
+    #[test]
+    fn test_find_dotenv_walks_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.path().join(".env"), "A=1").unwrap();
+        assert_eq!(find_dotenv(&nested), Some(dir.path().join(".env")));
+    }
+
+    #[test]
+    fn test_hash_content() {
+        assert_eq!(
+            hash_content(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_statement() {
+        assert_eq!(
+            statement(HookShell::Bash, "A", Some("it's")),
+            "export A='it'\\''s';\n"
+        );
+        assert_eq!(statement(HookShell::Zsh, "A", None), "unset A;\n");
+        assert_eq!(
+            statement(HookShell::Fish, "A", Some("it's \\")),
+            "set -gx A 'it\\'s \\\\';\n"
+        );
+        assert_eq!(statement(HookShell::Fish, "A", None), "set -e A;\n");
+    }
+
+    #[test]
+    fn test_export_statements_skips_unsafe_names() {
+        let change = |key: &str, new: Option<&str>| PlannedChange {
+            key: key.to_string(),
+            old: None,
+            new: new.map(str::to_string),
+        };
+        let changes = [
+            change("A$(id)", Some("1")),
+            change("GOOD", Some("2")),
+            change("BASH_FUNC_x%%", None),
+            change("GONE", None),
+        ];
+        assert_eq!(
+            export_statements(HookShell::Bash, &changes),
+            "export GOOD='2';\nunset GONE;\n"
+        );
+    }
+
+    #[test]
+    fn test_sync_statements_blocked_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".env"), "HOOK_BLOCKED=1").unwrap();
+        let result = sync_statements(
+            HookShell::Bash,
+            dir.path(),
+            None,
+            &Allowlist::default(),
+            |_| None,
+        );
+        assert_eq!(result, format!("unset {};\n", STATE_VARIABLE));
+    }
+
+    #[test]
+    fn test_sync_statements_load_and_unload() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".env");
+        fs::write(&file, "HOOK_NEW=1\nHOOK_EXISTING=2").unwrap();
+        let allowlist = allowlist_for(&file);
+        let lookup = |key: &str| (key == "HOOK_EXISTING").then(|| "old".to_string());
+
+        let loaded = sync_statements(HookShell::Bash, dir.path(), None, &allowlist, lookup);
+        assert!(loaded.contains("export HOOK_NEW='1';"));
+        assert!(loaded.contains("export HOOK_EXISTING='2';"));
+        let state = loaded
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("export {}='", STATE_VARIABLE)))
+            .and_then(|line| line.strip_suffix("';"))
+            .unwrap()
+            .to_string();
+
+        // Nothing changes while staying in the same directory
+        let same = sync_statements(
+            HookShell::Bash,
+            dir.path(),
+            Some(&state),
+            &allowlist,
+            lookup,
+        );
+        assert!(same.is_empty());
+
+        // Leaving directory restores previous values
+        let outside = tempfile::tempdir().unwrap();
+        let unloaded = sync_statements(
+            HookShell::Bash,
+            outside.path(),
+            Some(&state),
+            &allowlist,
+            lookup,
+        );
+        assert!(unloaded.contains("unset HOOK_NEW;"));
+        assert!(unloaded.contains("export HOOK_EXISTING='old';"));
+        assert!(unloaded.ends_with(&format!("unset {};\n", STATE_VARIABLE)));
+    }
+
+    #[test]
+    fn test_sync_statements_changed_content_requires_allow() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".env");
+        fs::write(&file, "HOOK_CHANGED=1").unwrap();
+        let allowlist = allowlist_for(&file);
+        fs::write(&file, "HOOK_CHANGED=2").unwrap();
+        let result = sync_statements(HookShell::Zsh, dir.path(), None, &allowlist, |_| None);
+        assert!(!result.contains("HOOK_CHANGED"));
+    }
+
+    #[test]
+    fn test_sync_statements_skips_invalid_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".env");
+        fs::write(&file, "X;touch pwned;Y=1\nHOOK_VALID=2").unwrap();
+        let allowlist = allowlist_for(&file);
+        let loaded = sync_statements(HookShell::Bash, dir.path(), None, &allowlist, |_| None);
+        assert!(!loaded.contains("pwned"));
+
+        // Names smuggled into state are never restored either
+        let state = HookState {
+            previous: BTreeMap::from([("A;touch pwned".to_string(), "1".to_string())]),
+            unset: vec!["B;touch pwned".to_string()],
+            ..Default::default()
+        };
+        let outside = tempfile::tempdir().unwrap();
+        let unloaded = sync_statements(
+            HookShell::Bash,
+            outside.path(),
+            Some(&state.encode()),
+            &allowlist,
+            |_| None,
+        );
+        assert_eq!(unloaded, format!("unset {};\n", STATE_VARIABLE));
+    }
+
+    #[test]
+    fn test_render_hook() {
+        assert!(render_hook(HookShell::Bash, "envfetch").contains("PROMPT_COMMAND"));
+        assert!(render_hook(HookShell::Zsh, "envfetch").contains("chpwd_functions"));
+        assert!(render_hook(HookShell::Fish, "envfetch").contains("--on-variable PWD"));
+    }
+}
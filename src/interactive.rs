@@ -1,22 +1,84 @@
+pub mod clipboard;
 pub mod controller;
+pub mod search;
 pub mod state;
 #[cfg(test)]
 pub mod tests;
 pub mod view;
 
-use crate::variables; // Function to get environment variables.
+use crate::models::{ErrorKind, InteractiveArgs};
+use crate::secrets::SecretFilter;
+use crate::variables::{self, PlannedChange}; // Function to get environment variables.
+use crate::{dotenv, utils};
+use crossterm::execute;
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::backend::CrosstermBackend;
 use ratatui::{Terminal, backend::Backend};
-use std::io;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Terminal interactive mode is drawn on.
+// Tests draw on TestBackend instead, so disable this useless warning
+#[allow(dead_code)]
+pub type ScreenTerminal = Terminal<CrosstermBackend<Box<dyn Write>>>;
+
+/// Get stream interface is drawn on, stderr keeps stdout free for printed statements.
+fn screen(on_stderr: bool) -> Box<dyn Write> {
+    if on_stderr {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    }
+}
+
+/// Switch terminal to raw mode and alternate screen, restoring it on panic.
+#[allow(dead_code)]
+pub fn init_terminal(on_stderr: bool) -> io::Result<ScreenTerminal> {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal(on_stderr);
+        hook(info);
+    }));
+    enable_raw_mode()?;
+    execute!(screen(on_stderr), EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(screen(on_stderr)))
+}
+
+/// Leave alternate screen and raw mode.
+pub fn restore_terminal(on_stderr: bool) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(screen(on_stderr), LeaveAlternateScreen)
+}
 
 pub struct InteractiveApp {
     state: state::AppState,
+    // Interface is drawn on stderr and pending changes are printed on quit.
+    export: bool,
 }
 
 impl InteractiveApp {
-    pub fn new() -> Self {
-        Self {
-            state: state::AppState::new(variables::get_variables()),
-        }
+    pub fn new(secrets: SecretFilter, args: &InteractiveArgs) -> Result<Self, ErrorKind> {
+        let file = args.file.as_ref().map(PathBuf::from);
+        let entries = match &file {
+            Some(file) => dotenv::read_variables(file)?,
+            None => variables::get_variables(),
+        };
+        let mut state = state::AppState::new(entries);
+        state.secrets = secrets;
+        state.validation = args.validation.unwrap_or_default();
+        state.file = file;
+        let export = args.export.is_some();
+        state.export_on_quit = export;
+        Ok(Self { state, export })
+    }
+
+    /// Changes to export when interactive mode was closed, applied to process or globally and pending ones.
+    pub fn exported_changes(&self) -> Vec<PlannedChange> {
+        self.state.session_changes()
     }
 
     pub fn run<B>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()>
@@ -28,10 +90,68 @@ impl InteractiveApp {
             {
                 self.state.should_quit = true;
             }
-            terminal.draw(|f| view::render(&self.state, f))?;
+            terminal.draw(|f| view::render(&mut self.state, f))?;
             // Handle input (this may update scrolling, reload, etc.)
             controller::handle_input(&mut self.state)?;
+            if let Some(text) = self.state.clipboard_requested.take() {
+                let mut out = screen(self.export);
+                write!(out, "{}", clipboard::osc52(&text))?;
+                out.flush()?;
+            }
+            if let Some(command) = self.state.run_requested.take() {
+                self.run_process(terminal, &command)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run command with pending changes applied, suspending the interface until user returns.
+    fn run_process<B>(&mut self, terminal: &mut Terminal<B>, command: &str) -> io::Result<()>
+    where
+        B: Backend,
+    {
+        restore_terminal(self.export)?;
+        // Commands started while editing dotenv file get all of its variables
+        let changes = match self.state.file {
+            Some(_) => self
+                .state
+                .working_entries()
+                .into_iter()
+                .map(|(key, value)| PlannedChange {
+                    key,
+                    old: None,
+                    new: Some(value),
+                })
+                .collect(),
+            None => self.state.staged.clone(),
+        };
+        let mut process = utils::shell_command(command);
+        for change in &changes {
+            match &change.new {
+                Some(value) => process.env(&change.key, value),
+                None => process.env_remove(&change.key),
+            };
+        }
+        // Output of command must not end up among printed statements
+        if self.export {
+            process.stdout(Stdio::from(io::stderr()));
         }
+        let status = process.status();
+        let mut out = screen(self.export);
+        write!(out, "\nPress Enter to return to envfetch")?;
+        out.flush()?;
+        io::stdin().lock().read_line(&mut String::new())?;
+        enable_raw_mode()?;
+        execute!(screen(self.export), EnterAlternateScreen)?;
+        terminal.clear()?;
+        let message = match status {
+            Ok(status) => match status.code() {
+                Some(code) => format!("Command exited with code {}", code),
+                None => "Command was terminated by signal".to_string(),
+            },
+            Err(err) => format!("Failed to run command: {}", err),
+        };
+        self.state.show_message(&message, Duration::from_secs(3));
         Ok(())
     }
 }
@@ -1,22 +1,43 @@
-use crate::interactive::state::{AppState, InputFocus, Mode};
-use crate::variables;
+use crate::config::get_history_file_path;
+use crate::dotenv;
+use crate::interactive::state::{AppState, BulkAction, InputFocus, Mode, SortOrder};
+use crate::models::ErrorKind;
+use crate::utils::{is_path_list, validate_var_name, validate_var_value};
+use crate::variables::{self, PlannedChange};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::env;
 use std::io;
+use std::path::Path;
 use std::time::Duration;
 
+/// Where pending changes are applied to.
+enum ApplyTarget<'a> {
+    Process,
+    Global,
+    File(&'a str),
+}
+
 // This function is used in interactive.rs, so disable this useless warning
 #[allow(dead_code)]
 pub fn handle_input(state: &mut AppState) -> io::Result<()> {
-    if event::poll(Duration::from_millis(100))? {
-        if let Event::Key(key_event) = event::read()? {
-            if key_event.kind == KeyEventKind::Press {
-                match state.mode.clone() {
-                    Mode::List => handle_list_mode(state, key_event),
-                    Mode::Add => handle_add_mode(state, key_event),
-                    Mode::Edit(_) => handle_edit_mode(state, key_event),
-                    Mode::Delete(_) => handle_delete_mode(state, key_event),
-                }
-            }
+    if event::poll(Duration::from_millis(100))?
+        && let Event::Key(key_event) = event::read()?
+        && key_event.kind == KeyEventKind::Press
+    {
+        match state.mode.clone() {
+            Mode::List => handle_list_mode(state, key_event),
+            Mode::Search => handle_search_mode(state, key_event),
+            Mode::Add => handle_add_mode(state, key_event),
+            Mode::Edit(_) => handle_edit_mode(state, key_event),
+            Mode::ListEdit(_) => handle_list_edit_mode(state, key_event),
+            Mode::Delete(_) => handle_delete_mode(state, key_event),
+            Mode::Review => handle_review_mode(state, key_event),
+            Mode::ApplyFile => handle_apply_file_mode(state, key_event),
+            Mode::SaveFile => handle_save_file_mode(state, key_event),
+            Mode::Run => handle_run_mode(state, key_event),
+            Mode::Bulk => handle_bulk_mode(state, key_event),
+            Mode::BulkExport | Mode::BulkRename => handle_bulk_input_mode(state, key_event),
+            Mode::BulkConfirm(_) => handle_bulk_confirm_mode(state, key_event),
         }
     }
 
@@ -24,18 +45,33 @@ pub fn handle_input(state: &mut AppState) -> io::Result<()> {
         state.reload();
     }
 
-    if let Some(expiry) = state.message_expiry {
-        if std::time::Instant::now() > expiry {
-            state.clear_message();
-        }
+    if let Some(expiry) = state.message_expiry
+        && std::time::Instant::now() > expiry
+    {
+        state.clear_message();
     }
     Ok(())
 }
 
 pub fn handle_list_mode(state: &mut AppState, key: KeyEvent) {
+    if state.jump_pending {
+        state.jump_pending = false;
+        if let KeyCode::Char(c) = key.code {
+            state.jump_to(c);
+        }
+        return;
+    }
     match key.code {
         KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            state.should_quit = true;
+            if !state.has_pending_changes() || state.confirm_quit || state.export_on_quit {
+                state.should_quit = true;
+            } else {
+                state.confirm_quit = true;
+                state.show_message(
+                    "Pending changes will be lost, press Ctrl+q again to quit or [w] to review them",
+                    Duration::from_secs(5),
+                );
+            }
         }
         KeyCode::Char('a') => {
             state.mode = Mode::Add;
@@ -44,38 +80,105 @@ pub fn handle_list_mode(state: &mut AppState, key: KeyEvent) {
             state.input_cursor_key = 0;
             state.input_cursor_value = 0;
             state.input_focus = InputFocus::Key;
+            state.input_global = false;
         }
-        KeyCode::Char('e') => {
-            if let Some((k, v)) = state.entries.get(state.current_index) {
-                state.mode = Mode::Edit(k.clone());
-                state.input_value = v.clone();
-                state.input_cursor_value = state.input_value.len();
+        KeyCode::Char('e') | KeyCode::Char('E') => {
+            if let Some((k, v)) = state.selected_entry().cloned() {
+                // Path lists are edited entry by entry, E edits them as plain text
+                if key.code == KeyCode::Char('e') && is_path_list(&k) {
+                    state.list_items = env::split_paths(&v)
+                        .map(|entry| entry.display().to_string())
+                        .collect();
+                    state.list_index = 0;
+                    state.list_scroll_offset = 0;
+                    state.list_adding = false;
+                    state.mode = Mode::ListEdit(k);
+                    return;
+                }
+                state.mode = Mode::Edit(k);
+                state.input_value = v;
+                state.input_cursor_value = state.input_value.chars().count();
+                state.input_global = false;
             }
         }
         KeyCode::Char('d') => {
-            if let Some((k, _)) = state.entries.get(state.current_index) {
-                state.mode = Mode::Delete(k.clone());
+            if let Some((k, _)) = state.selected_entry().cloned() {
+                state.mode = Mode::Delete(k);
+                state.input_global = false;
             }
         }
-        KeyCode::Down => {
-            if state.current_index < state.entries.len().saturating_sub(1) {
-                state.current_index += 1;
-                let visible = 10;
-                if state.current_index >= state.scroll_offset + visible {
-                    state.scroll_offset += 1;
-                }
+        KeyCode::Down | KeyCode::Char('j') => state.move_selection(1, false),
+        KeyCode::Up | KeyCode::Char('k') => state.move_selection(-1, false),
+        KeyCode::PageDown => state.move_selection(state.page_size() as isize, false),
+        KeyCode::PageUp => state.move_selection(-(state.page_size() as isize), false),
+        KeyCode::Home | KeyCode::Char('g') => state.select_position(0),
+        KeyCode::End | KeyCode::Char('G') => state.select_position(usize::MAX),
+        KeyCode::Char('f') => state.jump_pending = true,
+        KeyCode::Char('s') => state.toggle_sort(),
+        KeyCode::Char('/') => state.mode = Mode::Search,
+        KeyCode::Char('n') => state.move_selection(1, true),
+        KeyCode::Char('N') => state.move_selection(-1, true),
+        KeyCode::Esc => state.clear_filter(),
+        KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            state.request_reload();
+        }
+        KeyCode::F(5) => state.request_reload(),
+        KeyCode::Char('u') => undo(state),
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => redo(state),
+        KeyCode::Char('h') => state.show_history = !state.show_history,
+        KeyCode::Char('!') => {
+            state.input_value.clear();
+            state.input_cursor_value = 0;
+            state.mode = Mode::Run;
+        }
+        KeyCode::Char('S') => {
+            state.input_value = ".env".to_string();
+            state.input_cursor_value = state.input_value.chars().count();
+            state.mode = Mode::SaveFile;
+        }
+        KeyCode::Char(' ') => {
+            state.toggle_mark();
+            state.move_selection(1, false);
+        }
+        KeyCode::Char('b') => {
+            if state.marked_entries().is_empty() {
+                state.show_message("Mark variables with Space first", Duration::from_secs(2));
+            } else {
+                state.mode = Mode::Bulk;
             }
         }
-        KeyCode::Up => {
-            if state.current_index > 0 {
-                state.current_index -= 1;
-                if state.current_index < state.scroll_offset {
-                    state.scroll_offset = state.current_index;
-                }
+        KeyCode::Char('K') if state.file.is_some() => move_entry(state, false),
+        KeyCode::Char('J') if state.file.is_some() => move_entry(state, true),
+        KeyCode::Char('w') => {
+            if !state.has_pending_changes() {
+                state.show_message("No pending changes", Duration::from_secs(2));
+            } else {
+                state.mode = Mode::Review;
             }
         }
-        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            state.request_reload();
+        KeyCode::Char('v') => {
+            state.reveal_secrets = !state.reveal_secrets;
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_search_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => state.mode = Mode::List,
+        KeyCode::Down => state.move_selection(1, false),
+        KeyCode::Up => state.move_selection(-1, false),
+        KeyCode::Esc => {
+            state.clear_filter();
+            state.mode = Mode::List;
+        }
+        KeyCode::Backspace => {
+            state.filter.pop();
+            state.move_selection(0, false);
+        }
+        KeyCode::Char(c) => {
+            state.filter.push(c);
+            state.move_selection(0, false);
         }
         _ => {}
     }
@@ -85,178 +188,718 @@ pub fn handle_add_mode(state: &mut AppState, key: KeyEvent) {
     match key.code {
         KeyCode::Enter => {
             if !state.input_key.trim().is_empty() {
-                match variables::set_variable(
-                    state.input_key.trim(),
-                    state.input_value.trim(),
-                    true,
-                ) {
-                    Err(err) => state.show_message(
-                        &format!("Failed to add variable: {}", err),
-                        Duration::from_secs(2),
-                    ),
-                    Ok(_) => {
-                        state.entries.push((
-                            state.input_key.trim().to_string(),
-                            state.input_value.trim().to_string(),
-                        ));
-                        state.show_message("Variable added", Duration::from_secs(2));
-                    }
+                let key = state.input_key.trim().to_string();
+                let value = state.input_value.trim().to_string();
+                if let Err(err) = validate_change(state, &key, Some(&value)) {
+                    state.show_message(&err.to_string(), Duration::from_secs(3));
+                    return;
                 }
+                finish_change(state, &key, Some(value));
                 state.mode = Mode::List;
             } else {
                 state.show_message("Key cannot be empty", Duration::from_secs(2));
             }
         }
         KeyCode::Esc => state.mode = Mode::List,
+        KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => toggle_global(state),
         KeyCode::Tab => {
             state.input_focus = match state.input_focus {
                 InputFocus::Key => InputFocus::Value,
                 InputFocus::Value => InputFocus::Key,
             };
         }
-        KeyCode::Left => match state.input_focus {
-            InputFocus::Key => {
-                if state.input_cursor_key > 0 {
-                    state.input_cursor_key -= 1;
-                }
-            }
-            InputFocus::Value => {
-                if state.input_cursor_value > 0 {
-                    state.input_cursor_value -= 1;
-                }
-            }
+        _ => match state.input_focus {
+            InputFocus::Key => edit_input(&mut state.input_key, &mut state.input_cursor_key, key),
+            InputFocus::Value => edit_input_value(state, key),
         },
-        KeyCode::Right => match state.input_focus {
-            InputFocus::Key => {
-                if state.input_cursor_key < state.input_key.len() {
-                    state.input_cursor_key += 1;
+    }
+}
+
+pub fn handle_edit_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => {
+            if let Mode::Edit(key_name) = state.mode.clone() {
+                if state.value_of(&key_name).is_some() {
+                    let value = state.input_value.trim().to_string();
+                    if let Err(err) = validate_change(state, &key_name, Some(&value)) {
+                        state.show_message(&err.to_string(), Duration::from_secs(3));
+                        return;
+                    }
+                    finish_change(state, &key_name, Some(value));
                 }
+                state.mode = Mode::List;
             }
-            InputFocus::Value => {
-                if state.input_cursor_value < state.input_value.len() {
-                    state.input_cursor_value += 1;
+        }
+        KeyCode::Esc => state.mode = Mode::List,
+        KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => toggle_global(state),
+        _ => edit_input_value(state, key),
+    }
+}
+
+pub fn handle_list_edit_mode(state: &mut AppState, key: KeyEvent) {
+    if state.list_adding {
+        match key.code {
+            KeyCode::Enter => {
+                let entry = state.input_value.trim().to_string();
+                if !entry.is_empty() {
+                    let index = (state.list_index + 1).min(state.list_items.len());
+                    state.list_items.insert(index, entry);
+                    state.list_index = index;
                 }
+                state.list_adding = false;
             }
-        },
-        KeyCode::Backspace => match state.input_focus {
-            InputFocus::Key => {
-                if state.input_cursor_key > 0 {
-                    state.input_key.remove(state.input_cursor_key - 1);
-                    state.input_cursor_key -= 1;
+            KeyCode::Esc => state.list_adding = false,
+            _ => edit_input_value(state, key),
+        }
+        return;
+    }
+    let last = state.list_items.len().saturating_sub(1);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    match key.code {
+        KeyCode::Enter => {
+            if let Mode::ListEdit(key_name) = state.mode.clone() {
+                match env::join_paths(&state.list_items) {
+                    Err(err) => {
+                        state.show_message(
+                            &format!("Invalid path entry: {}", err),
+                            Duration::from_secs(2),
+                        );
+                        return;
+                    }
+                    Ok(value) => {
+                        let value = value.to_string_lossy().to_string();
+                        if let Err(err) = validate_change(state, &key_name, Some(&value)) {
+                            state.show_message(&err.to_string(), Duration::from_secs(3));
+                            return;
+                        }
+                        make_change(state, &key_name, Some(value));
+                    }
                 }
+                state.mode = Mode::List;
             }
-            InputFocus::Value => {
-                if state.input_cursor_value > 0 {
-                    state.input_value.remove(state.input_cursor_value - 1);
-                    state.input_cursor_value -= 1;
+        }
+        KeyCode::Esc => state.mode = Mode::List,
+        KeyCode::Char('K') => move_list_item(state, false),
+        KeyCode::Char('J') => move_list_item(state, true),
+        KeyCode::Up if shift => move_list_item(state, false),
+        KeyCode::Down if shift => move_list_item(state, true),
+        KeyCode::Up if state.list_index > 0 => state.list_index -= 1,
+        KeyCode::Down if state.list_index < last => state.list_index += 1,
+        KeyCode::Char('a') => {
+            state.input_value.clear();
+            state.input_cursor_value = 0;
+            state.list_adding = true;
+        }
+        KeyCode::Char('d') if !state.list_items.is_empty() => {
+            state.list_items.remove(state.list_index);
+            state.list_index = state
+                .list_index
+                .min(state.list_items.len().saturating_sub(1));
+        }
+        _ => {}
+    }
+}
+
+/// Swap selected path list entry with next or previous one, keeping it selected.
+fn move_list_item(state: &mut AppState, down: bool) {
+    let index = state.list_index;
+    if down && index + 1 < state.list_items.len() {
+        state.list_items.swap(index, index + 1);
+        state.list_index += 1;
+    } else if !down && index > 0 {
+        state.list_items.swap(index, index - 1);
+        state.list_index -= 1;
+    }
+}
+
+pub fn handle_delete_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') => {
+            if let Mode::Delete(key_name) = state.mode.clone() {
+                if let Err(err) = validate_change(state, &key_name, None) {
+                    state.show_message(&err.to_string(), Duration::from_secs(3));
+                    return;
                 }
+                finish_change(state, &key_name, None);
             }
-        },
-        KeyCode::Char(c) => match state.input_focus {
-            InputFocus::Key => {
-                state.input_key.insert(state.input_cursor_key, c);
-                state.input_cursor_key += 1;
+            state.mode = Mode::List;
+        }
+        KeyCode::Char('g') => toggle_global(state),
+        KeyCode::Char('n') | KeyCode::Esc => state.mode = Mode::List,
+        _ => {}
+    }
+}
+
+pub fn handle_review_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        // Changes of edited dotenv file are only saved back to it
+        KeyCode::Char('s') if state.file.is_some() => save_file(state),
+        KeyCode::Char('p') if state.file.is_none() => apply_staged(state, ApplyTarget::Process),
+        KeyCode::Char('g') if state.file.is_none() => apply_staged(state, ApplyTarget::Global),
+        KeyCode::Char('f') if state.file.is_none() => {
+            state.input_value = ".env".to_string();
+            state.input_cursor_value = state.input_value.chars().count();
+            state.mode = Mode::ApplyFile;
+        }
+        KeyCode::Char('x') => {
+            state.discard_staged();
+            state.show_message("Pending changes discarded", Duration::from_secs(2));
+            state.mode = Mode::List;
+        }
+        KeyCode::Esc => state.mode = Mode::List,
+        _ => {}
+    }
+}
+
+pub fn handle_apply_file_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => {
+            let path = state.input_value.trim().to_string();
+            if path.is_empty() {
+                state.show_message("File name cannot be empty", Duration::from_secs(2));
+            } else {
+                apply_staged(state, ApplyTarget::File(&path));
             }
-            InputFocus::Value => {
-                state.input_value.insert(state.input_cursor_value, c);
-                state.input_cursor_value += 1;
+        }
+        KeyCode::Esc => state.mode = Mode::Review,
+        _ => edit_input_value(state, key),
+    }
+}
+
+pub fn handle_save_file_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => {
+            let path = state.input_value.trim().to_string();
+            if path.is_empty() {
+                state.show_message("File name cannot be empty", Duration::from_secs(2));
+            } else {
+                save_environment(state, &path);
             }
-        },
-        _ => {}
+        }
+        KeyCode::Esc => state.mode = Mode::List,
+        _ => edit_input_value(state, key),
     }
 }
 
-pub fn handle_edit_mode(state: &mut AppState, key: KeyEvent) {
+pub fn handle_run_mode(state: &mut AppState, key: KeyEvent) {
     match key.code {
         KeyCode::Enter => {
-            if let Mode::Edit(ref key_name) = state.mode {
-                if let Some(entry) = state.entries.iter_mut().find(|(k, _)| k == key_name) {
-                    match variables::set_variable(key_name, state.input_value.trim(), true) {
-                        Err(err) => state.show_message(
-                            &format!("Failed to update variable: {}", err),
-                            Duration::from_secs(2),
-                        ),
-                        Ok(_) => {
-                            entry.1 = state.input_value.trim().to_string();
-                            state.show_message("Variable updated", Duration::from_secs(2))
-                        }
-                    }
-                }
+            let command = state.input_value.trim().to_string();
+            if command.is_empty() {
+                state.show_message("Command cannot be empty", Duration::from_secs(2));
+            } else {
+                state.run_requested = Some(command);
                 state.mode = Mode::List;
             }
         }
         KeyCode::Esc => state.mode = Mode::List,
-        KeyCode::Left => {
-            if state.input_cursor_value > 0 {
-                state.input_cursor_value -= 1;
+        _ => edit_input_value(state, key),
+    }
+}
+
+/// Move selected variable of edited file, if it's listed in file order.
+fn move_entry(state: &mut AppState, down: bool) {
+    if state.sort_order != SortOrder::Environment {
+        state.show_message(
+            "Switch to environment order to move variables",
+            Duration::from_secs(2),
+        );
+        return;
+    }
+    state.move_entry(down);
+}
+
+/// Write variables to the edited dotenv file in the order they are listed.
+fn save_file(state: &mut AppState) {
+    let Some(file) = state.file.clone() else {
+        return;
+    };
+    if let Err(err) = dotenv::write_variables(&file, &state.working_entries()) {
+        state.show_message(
+            &format!("Failed to save file: {}", err),
+            Duration::from_secs(3),
+        );
+        return;
+    }
+    state.finish_apply();
+    state.order_changed = false;
+    state.confirm_quit = false;
+    state.mode = Mode::List;
+    state.show_message(&format!("Saved {}", file.display()), Duration::from_secs(2));
+}
+
+pub fn handle_bulk_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('d') => state.mode = Mode::BulkConfirm(BulkAction::Delete),
+        KeyCode::Char('e') => {
+            state.input_value = ".env".to_string();
+            state.input_cursor_value = state.input_value.chars().count();
+            state.mode = Mode::BulkExport;
+        }
+        KeyCode::Char('c') => state.mode = Mode::BulkConfirm(BulkAction::CopyNames),
+        KeyCode::Char('v') => state.mode = Mode::BulkConfirm(BulkAction::CopyValues),
+        KeyCode::Char('r') => {
+            state.input_value.clear();
+            state.input_cursor_value = 0;
+            state.mode = Mode::BulkRename;
+        }
+        KeyCode::Esc => state.mode = Mode::List,
+        _ => {}
+    }
+}
+
+pub fn handle_bulk_input_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => {
+            let input = state.input_value.trim().to_string();
+            if input.is_empty() {
+                state.show_message("Input cannot be empty", Duration::from_secs(2));
+            } else if state.mode == Mode::BulkExport {
+                state.mode = Mode::BulkConfirm(BulkAction::Export(input));
+            } else if let Err(message) = rename_targets(state, &input) {
+                state.show_message(&message, Duration::from_secs(3));
+            } else {
+                state.mode = Mode::BulkConfirm(BulkAction::Rename(input));
             }
         }
-        KeyCode::Right => {
-            if state.input_cursor_value < state.input_value.len() {
-                state.input_cursor_value += 1;
+        KeyCode::Esc => state.mode = Mode::Bulk,
+        _ => edit_input_value(state, key),
+    }
+}
+
+pub fn handle_bulk_confirm_mode(state: &mut AppState, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') => {
+            if let Mode::BulkConfirm(action) = state.mode.clone() {
+                state.mode = Mode::List;
+                apply_bulk(state, &action);
             }
         }
-        KeyCode::Backspace => {
-            if state.input_cursor_value > 0 {
-                state.input_value.remove(state.input_cursor_value - 1);
-                state.input_cursor_value -= 1;
+        KeyCode::Char('n') | KeyCode::Esc => state.mode = Mode::Bulk,
+        _ => {}
+    }
+}
+
+/// Apply operation to all marked variables and unmark them, keeping them marked on failure.
+fn apply_bulk(state: &mut AppState, action: &BulkAction) {
+    let entries = state.marked_entries();
+    let message = match action {
+        BulkAction::Delete => {
+            let changes: Vec<(String, Option<String>)> =
+                entries.iter().map(|(key, _)| (key.clone(), None)).collect();
+            make_changes(state, &changes);
+            format!(
+                "Deleted {} variable(s), press [w] to review pending changes",
+                entries.len()
+            )
+        }
+        BulkAction::Export(path) => {
+            let changes: Vec<PlannedChange> = entries
+                .iter()
+                .map(|(key, value)| PlannedChange {
+                    key: key.clone(),
+                    old: None,
+                    new: Some(value.clone()),
+                })
+                .collect();
+            if let Err(err) = dotenv::write_changes(Path::new(path), &changes) {
+                state.show_message(
+                    &format!("Failed to export variables: {}", err),
+                    Duration::from_secs(3),
+                );
+                return;
             }
+            format!("Exported {} variable(s) to {}", entries.len(), path)
+        }
+        BulkAction::CopyNames => {
+            let names: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+            state.clipboard_requested = Some(names.join("\n"));
+            format!("Copied names of {} variable(s)", entries.len())
+        }
+        BulkAction::CopyValues => {
+            let lines: Vec<String> = entries
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            state.clipboard_requested = Some(lines.join("\n"));
+            format!("Copied {} variable(s) with values", entries.len())
+        }
+        BulkAction::Rename(prefix) => {
+            let targets = match rename_targets(state, prefix) {
+                Ok(targets) => targets,
+                Err(message) => {
+                    state.show_message(&message, Duration::from_secs(3));
+                    return;
+                }
+            };
+            // Deleting first keeps the new names when renamed variables are set
+            let deletes = targets.iter().map(|(key, _, _)| (key.clone(), None));
+            let sets = targets
+                .iter()
+                .map(|(_, renamed, value)| (renamed.clone(), Some(value.clone())));
+            let changes: Vec<(String, Option<String>)> = deletes.chain(sets).collect();
+            make_changes(state, &changes);
+            format!(
+                "Renamed {} variable(s), press [w] to review pending changes",
+                entries.len()
+            )
+        }
+    };
+    state.marked.clear();
+    state.show_message(&message, Duration::from_secs(3));
+}
+
+/// Get marked variables with their new names and values for rename with given prefix.
+///
+/// Fails if new name is invalid or is name of another marked variable, which
+/// would lose value of one of them.
+fn rename_targets(state: &AppState, prefix: &str) -> Result<Vec<(String, String, String)>, String> {
+    let mut targets = vec![];
+    for (key, value) in state.marked_entries() {
+        let renamed = format!("{}{}", prefix, key);
+        validate_var_name(&renamed, state.validation)?;
+        if state.marked.contains(&renamed) {
+            return Err(format!(
+                "Can't rename {} to {}, it's marked too",
+                key, renamed
+            ));
+        }
+        targets.push((key, renamed, value));
+    }
+    Ok(targets)
+}
+
+/// Move cursor in or edit input_value of single field prompts.
+fn edit_input_value(state: &mut AppState, key: KeyEvent) {
+    edit_input(&mut state.input_value, &mut state.input_cursor_value, key);
+}
+
+/// Move cursor in or edit input field, cursor counts characters rather than bytes.
+fn edit_input(input: &mut String, cursor: &mut usize, key: KeyEvent) {
+    match key.code {
+        KeyCode::Left if *cursor > 0 => *cursor -= 1,
+        KeyCode::Right if *cursor < input.chars().count() => *cursor += 1,
+        KeyCode::Backspace if *cursor > 0 => {
+            *cursor -= 1;
+            input.remove(byte_offset(input, *cursor));
         }
         KeyCode::Char(c) => {
-            state.input_value.insert(state.input_cursor_value, c);
-            state.input_cursor_value += 1;
+            input.insert(byte_offset(input, *cursor), c);
+            *cursor += 1;
         }
         _ => {}
     }
 }
 
-pub fn handle_delete_mode(state: &mut AppState, key: KeyEvent) {
-    match key.code {
-        KeyCode::Char('y') => {
-            if let Mode::Delete(ref key_name) = state.mode {
-                match variables::delete_variable(key_name.to_owned(), true) {
-                    Err(err) => state.show_message(
-                        &format!("Failed to delete variable: {}", err),
-                        Duration::from_secs(2),
-                    ),
-                    Ok(_) => {
-                        state.entries.retain(|(k, _)| k != key_name);
-                        state.show_message("Variable deleted", Duration::from_secs(2))
-                    }
+/// Get byte offset of character at given position, or length of input if it's past the end.
+fn byte_offset(input: &str, position: usize) -> usize {
+    input
+        .char_indices()
+        .nth(position)
+        .map_or(input.len(), |(offset, _)| offset)
+}
+
+/// Save all variables with pending changes applied to dotenv file, keeping its other lines.
+///
+/// Variables that can't be represented in dotenv file are skipped.
+fn save_environment(state: &mut AppState, path: &str) {
+    let mut changes = vec![];
+    let mut skipped = 0;
+    for (key, _) in &state.entries {
+        let new = state.working_value(key);
+        if let Some(value) = &new
+            && dotenv::format_line(key, value).is_err()
+        {
+            skipped += 1;
+            continue;
+        }
+        changes.push(PlannedChange {
+            key: key.clone(),
+            old: None,
+            new,
+        });
+    }
+    if let Err(err) = dotenv::write_changes(Path::new(path), &changes) {
+        state.show_message(
+            &format!("Failed to save environment: {}", err),
+            Duration::from_secs(3),
+        );
+        return;
+    }
+    let saved = changes.iter().filter(|change| change.new.is_some()).count();
+    let mut message = format!("Saved {} variable(s) to {}", saved, path);
+    if skipped > 0 {
+        message.push_str(&format!(
+            ", skipped {} that can't be written to dotenv file",
+            skipped
+        ));
+    }
+    state.mode = Mode::List;
+    state.show_message(&message, Duration::from_secs(3));
+}
+
+/// Apply all pending changes and go back to the list, staying in review mode on failure.
+///
+/// Changes are applied to environment one by one, so those that succeeded before
+/// a failure are no longer pending and aren't applied twice on retry.
+fn apply_staged(state: &mut AppState, target: ApplyTarget) {
+    let count = state.staged.len();
+    // Nothing is applied if any change is invalid, so environment is never partially changed by it
+    if let Some((change, err)) = state.staged.iter().find_map(|change| {
+        validate_change(state, &change.key, change.new.as_deref())
+            .err()
+            .map(|err| (change, err))
+    }) {
+        let message = format!("Can't apply change of {}: {}", change.key, err);
+        state.show_message(&message, Duration::from_secs(3));
+        return;
+    }
+    let result = match target {
+        ApplyTarget::Process | ApplyTarget::Global => {
+            let global = matches!(target, ApplyTarget::Global);
+            loop {
+                let Some(change) = state.staged.first().cloned() else {
+                    break Ok(());
+                };
+                let result = if global {
+                    (state.global_applier)(&change)
+                } else {
+                    variables::apply_change(&change, false, &get_history_file_path())
+                };
+                if let Err(err) = result {
+                    break Err(format!("Failed to apply change of {}: {}", change.key, err));
                 }
+                state.record_applied(&change);
+                state.finish_apply_of(&change.key);
             }
-            state.mode = Mode::List;
         }
-        KeyCode::Char('n') | KeyCode::Esc => state.mode = Mode::List,
-        _ => {}
+        ApplyTarget::File(path) => dotenv::write_changes(Path::new(path), &state.staged)
+            .map_err(|err| format!("Failed to apply changes: {}", err)),
+    };
+    if let Err(message) = result {
+        let applied = count - state.staged.len();
+        let message = if applied > 0 {
+            format!("{}, {} change(s) before it were applied", message, applied)
+        } else {
+            message
+        };
+        state.move_selection(0, false);
+        state.show_message(&message, Duration::from_secs(3));
+        return;
+    }
+    let destination = match target {
+        ApplyTarget::Process => "process environment".to_string(),
+        ApplyTarget::Global => "global environment".to_string(),
+        ApplyTarget::File(path) => path.to_string(),
+    };
+    // Writing a file leaves the environment as it was, so the list shows original values again
+    if let ApplyTarget::File(_) = target {
+        state.discard_staged();
+    } else {
+        state.finish_apply();
+    }
+    state.confirm_quit = false;
+    state.mode = Mode::List;
+    state.show_message(
+        &format!("Applied {} change(s) to {}", count, destination),
+        Duration::from_secs(2),
+    );
+}
+
+/// Choose whether operation in the modal is applied globally, edited dotenv file is never global.
+fn toggle_global(state: &mut AppState) {
+    if state.file.is_none() {
+        state.input_global = !state.input_global;
+    }
+}
+
+/// Check name and value of change with the configured validation profile.
+fn validate_change(state: &AppState, key: &str, value: Option<&str>) -> Result<(), ErrorKind> {
+    validate_var_name(key, state.validation).map_err(ErrorKind::NameValidationError)?;
+    if let Some(value) = value {
+        validate_var_value(value).map_err(ErrorKind::ValueValidationError)?;
+    }
+    Ok(())
+}
+
+/// Finish add, edit or delete, applying it globally if that was chosen in the modal.
+fn finish_change(state: &mut AppState, key: &str, value: Option<String>) {
+    if state.input_global && state.file.is_none() {
+        make_global_change(state, key, value);
+    } else {
+        make_change(state, key, value);
+    }
+}
+
+/// Set variable globally, or delete it if value is missing, right away instead of staging it.
+///
+/// Global changes are recorded in history, so they are reverted with undo command
+/// instead of undo of the session, which forgets earlier changes of the variable.
+fn make_global_change(state: &mut AppState, key: &str, value: Option<String>) {
+    if let Err(err) = validate_change(state, key, value.as_deref()) {
+        state.show_message(&err.to_string(), Duration::from_secs(5));
+        return;
+    }
+    let change = PlannedChange {
+        key: key.to_string(),
+        old: state.working_value(key),
+        new: value,
+    };
+    let (action, done) = match (&change.old, &change.new) {
+        (None, _) => ("add", "added"),
+        (_, None) => ("delete", "deleted"),
+        _ => ("update", "updated"),
+    };
+    if let Err(err) = (state.global_applier)(&change) {
+        state.show_message(
+            &format!("Failed to {} variable: {}", action, err),
+            Duration::from_secs(5),
+        );
+        return;
+    }
+    state.record_applied(&change);
+    state.staged.retain(|c| c.key != key);
+    state
+        .undo_stack
+        .retain(|changes| changes.iter().all(|c| c.key != key));
+    state
+        .redo_stack
+        .retain(|changes| changes.iter().all(|c| c.key != key));
+    state.set_entry(key, change.new);
+    state.mark_modified(key);
+    state.show_message(
+        &format!("Variable {} globally", done),
+        Duration::from_secs(2),
+    );
+}
+
+/// Stage setting variable, or deleting it if value is missing, and remember change for undo.
+fn make_change(state: &mut AppState, key: &str, value: Option<String>) {
+    let changes = make_changes(state, &[(key.to_string(), value)]);
+    let done = match changes.first().map(|change| (&change.old, &change.new)) {
+        None => {
+            state.show_message("Nothing changed", Duration::from_secs(2));
+            return;
+        }
+        Some((None, _)) => "added",
+        Some((_, None)) => "deleted",
+        Some(_) => "updated",
+    };
+    state.show_message(
+        &format!("Variable {}, press [w] to review pending changes", done),
+        Duration::from_secs(2),
+    );
+}
+
+/// Stage changes of one operation in given order and remember them for undo together.
+///
+/// Returns changes that were staged, leaving out those that don't change anything.
+fn make_changes(state: &mut AppState, changes: &[(String, Option<String>)]) -> Vec<PlannedChange> {
+    let mut staged = vec![];
+    for (key, value) in changes {
+        let change = PlannedChange {
+            key: key.clone(),
+            old: state.working_value(key),
+            new: value.clone(),
+        };
+        if change.old != change.new {
+            stage_change(state, &change);
+            staged.push(change);
+        }
+    }
+    if !staged.is_empty() {
+        state.undo_stack.push(staged.clone());
+        state.redo_stack.clear();
+    }
+    staged
+}
+
+/// Revert the most recent operation.
+fn undo(state: &mut AppState) {
+    let Some(changes) = state.undo_stack.pop() else {
+        state.show_message("Nothing to undo", Duration::from_secs(2));
+        return;
+    };
+    for change in changes.iter().rev() {
+        let reverse = PlannedChange {
+            key: change.key.clone(),
+            old: change.new.clone(),
+            new: change.old.clone(),
+        };
+        stage_change(state, &reverse);
+    }
+    state.show_message(
+        &format!("Undid change of {}", describe_keys(&changes)),
+        Duration::from_secs(2),
+    );
+    state.redo_stack.push(changes);
+}
+
+/// Make the most recently undone operation again.
+fn redo(state: &mut AppState) {
+    let Some(changes) = state.redo_stack.pop() else {
+        state.show_message("Nothing to redo", Duration::from_secs(2));
+        return;
+    };
+    for change in &changes {
+        stage_change(state, change);
+    }
+    state.show_message(
+        &format!("Redid change of {}", describe_keys(&changes)),
+        Duration::from_secs(2),
+    );
+    state.undo_stack.push(changes);
+}
+
+/// Name changed variable, or count them if operation changed several.
+fn describe_keys(changes: &[PlannedChange]) -> String {
+    match changes {
+        [change] => change.key.clone(),
+        _ => format!("{} variables", changes.len()),
     }
 }
 
+/// Add change to pending changes shown in the list.
+fn stage_change(state: &mut AppState, change: &PlannedChange) {
+    state.stage(&change.key, change.new.clone());
+    state.mark_modified(&change.key);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::ValidationProfile;
     use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
     use std::time::Duration;
 
     // This test-only function lets us inject a simulated event, covering branches in handle_input.
     pub fn handle_input_with_event(state: &mut AppState, evt: Option<Event>) -> io::Result<()> {
-        if let Some(Event::Key(key_event)) = evt {
-            if key_event.kind == KeyEventKind::Press {
-                match state.mode.clone() {
-                    Mode::List => handle_list_mode(state, key_event),
-                    Mode::Add => handle_add_mode(state, key_event),
-                    Mode::Edit(_) => handle_edit_mode(state, key_event),
-                    Mode::Delete(_) => handle_delete_mode(state, key_event),
-                }
+        if let Some(Event::Key(key_event)) = evt
+            && key_event.kind == KeyEventKind::Press
+        {
+            match state.mode.clone() {
+                Mode::List => handle_list_mode(state, key_event),
+                Mode::Search => handle_search_mode(state, key_event),
+                Mode::Add => handle_add_mode(state, key_event),
+                Mode::Edit(_) => handle_edit_mode(state, key_event),
+                Mode::ListEdit(_) => handle_list_edit_mode(state, key_event),
+                Mode::Delete(_) => handle_delete_mode(state, key_event),
+                Mode::Review => handle_review_mode(state, key_event),
+                Mode::ApplyFile => handle_apply_file_mode(state, key_event),
+                Mode::SaveFile => handle_save_file_mode(state, key_event),
+                Mode::Run => handle_run_mode(state, key_event),
+                Mode::Bulk => handle_bulk_mode(state, key_event),
+                Mode::BulkExport | Mode::BulkRename => handle_bulk_input_mode(state, key_event),
+                Mode::BulkConfirm(_) => handle_bulk_confirm_mode(state, key_event),
             }
         }
         if state.reload_requested {
             state.reload();
         }
-        if let Some(expiry) = state.message_expiry {
-            if std::time::Instant::now() > expiry {
-                state.clear_message();
-            }
+        if let Some(expiry) = state.message_expiry
+            && std::time::Instant::now() > expiry
+        {
+            state.clear_message();
         }
         Ok(())
     }
@@ -402,11 +1045,924 @@ mod tests {
     #[test]
     fn test_handle_list_mode_reload() {
         let mut state = AppState::new(vec![]);
-        let key_event = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        let key_event = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL);
         handle_list_mode(&mut state, key_event);
         assert!(state.reload_requested);
     }
 
+    fn search_state() -> AppState {
+        AppState::new(vec![
+            ("HOME".to_string(), "/root".to_string()),
+            ("PATH".to_string(), "/usr/share/bin".to_string()),
+            ("SHELL".to_string(), "/bin/sh".to_string()),
+            ("USER".to_string(), "root".to_string()),
+        ])
+    }
+
+    fn press(state: &mut AppState, code: KeyCode) {
+        let key_event = KeyEvent::new(code, KeyModifiers::empty());
+        match state.mode {
+            Mode::Search => handle_search_mode(state, key_event),
+            Mode::Review => handle_review_mode(state, key_event),
+            Mode::ApplyFile => handle_apply_file_mode(state, key_event),
+            Mode::SaveFile => handle_save_file_mode(state, key_event),
+            Mode::Run => handle_run_mode(state, key_event),
+            Mode::Bulk => handle_bulk_mode(state, key_event),
+            Mode::BulkExport | Mode::BulkRename => handle_bulk_input_mode(state, key_event),
+            Mode::BulkConfirm(_) => handle_bulk_confirm_mode(state, key_event),
+            _ => handle_list_mode(state, key_event),
+        }
+    }
+
+    fn many_entries() -> AppState {
+        let mut state = AppState::new(
+            (0..30)
+                .map(|i| (format!("VAR{:02}", i), "x".repeat(30 - i)))
+                .collect(),
+        );
+        state.list_height = 10;
+        state
+    }
+
+    #[test]
+    fn test_handle_list_mode_paging() {
+        let mut state = many_entries();
+        press(&mut state, KeyCode::PageDown);
+        assert_eq!(state.current_index, 10);
+        assert_eq!(state.scroll_offset, 1);
+        press(&mut state, KeyCode::End);
+        assert_eq!(state.current_index, 29);
+        assert_eq!(state.scroll_offset, 20);
+        press(&mut state, KeyCode::PageUp);
+        assert_eq!(state.current_index, 19);
+        assert_eq!(state.scroll_offset, 19);
+        press(&mut state, KeyCode::Char('g'));
+        assert_eq!(state.current_index, 0);
+        assert_eq!(state.scroll_offset, 0);
+        press(&mut state, KeyCode::Char('G'));
+        assert_eq!(state.current_index, 29);
+        press(&mut state, KeyCode::Home);
+        assert_eq!(state.current_index, 0);
+        press(&mut state, KeyCode::PageUp);
+        assert_eq!(state.current_index, 0);
+    }
+
+    #[test]
+    fn test_handle_list_mode_vim_keys() {
+        let mut state = many_entries();
+        press(&mut state, KeyCode::Char('j'));
+        press(&mut state, KeyCode::Char('j'));
+        assert_eq!(state.current_index, 2);
+        press(&mut state, KeyCode::Char('k'));
+        assert_eq!(state.current_index, 1);
+    }
+
+    #[test]
+    fn test_handle_list_mode_jump_to_letter() {
+        let mut state = search_state();
+        press(&mut state, KeyCode::Char('f'));
+        assert!(state.jump_pending);
+        press(&mut state, KeyCode::Char('s'));
+        assert!(!state.jump_pending);
+        assert_eq!(state.current_index, 2);
+        assert_eq!(state.sort_order, SortOrder::Environment);
+        press(&mut state, KeyCode::Char('f'));
+        press(&mut state, KeyCode::Char('h'));
+        assert_eq!(state.current_index, 0);
+        // No variable starts with z, selection is kept
+        press(&mut state, KeyCode::Char('f'));
+        press(&mut state, KeyCode::Char('z'));
+        assert_eq!(state.current_index, 0);
+    }
+
+    #[test]
+    fn test_handle_list_mode_sort() {
+        let mut state = AppState::new(vec![
+            ("B".to_string(), "long value".to_string()),
+            ("C".to_string(), "v".to_string()),
+            ("A".to_string(), "value".to_string()),
+        ]);
+        state.current_index = 1;
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.sort_order, SortOrder::Name);
+        assert_eq!(state.visible_entries(), vec![2, 0, 1]);
+        // Selection stays on the same variable
+        assert_eq!(state.current_index, 1);
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.sort_order, SortOrder::ValueLength);
+        assert_eq!(state.visible_entries(), vec![1, 2, 0]);
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.sort_order, SortOrder::Modified);
+        state.mark_modified("C");
+        state.mark_modified("B");
+        assert_eq!(state.visible_entries(), vec![0, 1, 2]);
+        state.mark_modified("C");
+        assert_eq!(state.visible_entries(), vec![1, 0, 2]);
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.sort_order, SortOrder::Environment);
+        assert_eq!(state.visible_entries(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut state = AppState::new(vec![("A".to_string(), "1".to_string())]);
+        make_change(&mut state, "A", Some("2".to_string()));
+        make_change(&mut state, "B", Some("3".to_string()));
+        make_change(&mut state, "A", None);
+        // Deleted variable stays in the list with its original value until applied
+        assert_eq!(
+            state.entries,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "3".to_string())
+            ]
+        );
+        assert_eq!(state.working_value("A"), None);
+
+        press(&mut state, KeyCode::Char('u'));
+        assert_eq!(state.working_value("A"), Some("2".to_string()));
+        press(&mut state, KeyCode::Char('u'));
+        assert_eq!(state.working_value("B"), None);
+        assert_eq!(state.redo_stack.len(), 2);
+
+        let redo = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        handle_list_mode(&mut state, redo);
+        assert_eq!(state.working_value("B"), Some("3".to_string()));
+        assert_eq!(state.message, Some("Redid change of B".to_string()));
+
+        // New change drops changes that could be redone
+        make_change(&mut state, "C", Some("4".to_string()));
+        assert!(state.redo_stack.is_empty());
+        handle_list_mode(&mut state, redo);
+        assert_eq!(state.message, Some("Nothing to redo".to_string()));
+
+        for _ in 0..4 {
+            press(&mut state, KeyCode::Char('u'));
+        }
+        assert_eq!(state.entries, vec![("A".to_string(), "1".to_string())]);
+        assert!(state.staged.is_empty());
+        press(&mut state, KeyCode::Char('u'));
+        assert_eq!(state.message, Some("Nothing to undo".to_string()));
+    }
+
+    #[test]
+    fn test_staged_changes_review_and_discard() {
+        let mut state = AppState::new(vec![
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "2".to_string()),
+        ]);
+        press(&mut state, KeyCode::Char('w'));
+        assert_eq!(state.mode, Mode::List);
+        assert_eq!(state.message, Some("No pending changes".to_string()));
+
+        make_change(&mut state, "A", Some("3".to_string()));
+        make_change(&mut state, "B", None);
+        make_change(&mut state, "C", Some("4".to_string()));
+        // Changing back to the original value unstages the change
+        make_change(&mut state, "A", Some("1".to_string()));
+        make_change(&mut state, "A", Some("5".to_string()));
+        let keys: Vec<&str> = state.staged.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, vec!["B", "C", "A"]);
+        assert_eq!(state.staged_change("A").unwrap().old, Some("1".to_string()));
+
+        // Adding and then deleting variable leaves nothing pending
+        make_change(&mut state, "C", None);
+        assert!(state.staged_change("C").is_none());
+        assert_eq!(state.value_of("C"), None);
+
+        press(&mut state, KeyCode::Char('w'));
+        assert_eq!(state.mode, Mode::Review);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.mode, Mode::List);
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('x'));
+        assert_eq!(state.mode, Mode::List);
+        assert!(state.staged.is_empty());
+        assert!(state.undo_stack.is_empty());
+        assert_eq!(
+            state.entries,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "2".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_staged_to_process() {
+        unsafe { env::set_var("TEST_STAGED_REMOVED", "old") };
+        let mut state = AppState::new(vec![
+            ("TEST_STAGED_REMOVED".to_string(), "old".to_string()),
+            ("TEST_STAGED_KEPT".to_string(), "kept".to_string()),
+        ]);
+        make_change(&mut state, "TEST_STAGED_ADDED", Some("new".to_string()));
+        make_change(&mut state, "TEST_STAGED_REMOVED", None);
+        assert_eq!(env::var("TEST_STAGED_ADDED").ok(), None);
+        assert_eq!(
+            env::var("TEST_STAGED_REMOVED").ok(),
+            Some("old".to_string())
+        );
+
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('p'));
+        assert_eq!(state.mode, Mode::List);
+        assert_eq!(
+            state.message,
+            Some("Applied 2 change(s) to process environment".to_string())
+        );
+        assert_eq!(env::var("TEST_STAGED_ADDED").ok(), Some("new".to_string()));
+        assert_eq!(env::var("TEST_STAGED_REMOVED").ok(), None);
+        assert!(state.staged.is_empty());
+        assert_eq!(state.value_of("TEST_STAGED_REMOVED"), None);
+        unsafe { env::remove_var("TEST_STAGED_ADDED") };
+    }
+
+    #[test]
+    fn test_apply_staged_globally_keeps_failed_changes() {
+        let mut state = AppState::new(vec![("A".to_string(), "1".to_string())])
+            .with_global_applier(|change| match change.key.as_str() {
+                "C" => Err(ErrorKind::CannotSetVariableGlobally("denied".to_string())),
+                _ => Ok(()),
+            });
+        make_change(&mut state, "A", None);
+        make_change(&mut state, "B", Some("2".to_string()));
+        make_change(&mut state, "C", Some("3".to_string()));
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('g'));
+        assert_eq!(state.mode, Mode::Review);
+        assert!(state.message.as_ref().is_some_and(|message| {
+            message.starts_with("Failed to apply change of C:")
+                && message.ends_with(", 2 change(s) before it were applied")
+        }));
+        let keys: Vec<&str> = state.staged.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, vec!["C"]);
+        assert_eq!(state.value_of("A"), None);
+    }
+
+    #[test]
+    fn test_apply_staged_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "# keep\nA=1\nB=2\n").unwrap();
+        let mut state = AppState::new(vec![
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "2".to_string()),
+        ]);
+        make_change(&mut state, "A", Some("two words".to_string()));
+        make_change(&mut state, "B", None);
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('f'));
+        assert_eq!(state.mode, Mode::ApplyFile);
+        assert_eq!(state.input_value, ".env");
+        state.input_value = path.display().to_string();
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.mode, Mode::List);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# keep\nA='two words'\n"
+        );
+        // Environment is left as it was
+        assert!(state.staged.is_empty());
+        assert_eq!(state.value_of("A"), Some("1".to_string()));
+        assert_eq!(state.value_of("B"), Some("2".to_string()));
+    }
+
+    #[test]
+    fn test_run_mode_requests_command() {
+        let mut state = AppState::new(vec![]);
+        press(&mut state, KeyCode::Char('!'));
+        assert_eq!(state.mode, Mode::Run);
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.mode, Mode::Run);
+        assert_eq!(state.message, Some("Command cannot be empty".to_string()));
+        for c in "env".chars() {
+            press(&mut state, KeyCode::Char(c));
+        }
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.mode, Mode::List);
+        assert_eq!(state.run_requested, Some("env".to_string()));
+    }
+
+    #[test]
+    fn test_save_environment_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "# saved\nB=old\n").unwrap();
+        let mut state = AppState::new(vec![
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "2".to_string()),
+            ("MULTILINE".to_string(), "a\nb".to_string()),
+        ]);
+        make_change(&mut state, "A", Some("3".to_string()));
+        make_change(&mut state, "B", None);
+        press(&mut state, KeyCode::Char('S'));
+        assert_eq!(state.mode, Mode::SaveFile);
+        state.input_value = path.display().to_string();
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.mode, Mode::List);
+        assert_eq!(
+            state.message,
+            Some(format!(
+                "Saved 1 variable(s) to {}, skipped 1 that can't be written to dotenv file",
+                path.display()
+            ))
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# saved\nA=3\n");
+        // Saving doesn't apply pending changes
+        assert_eq!(state.staged.len(), 2);
+    }
+
+    #[test]
+    fn test_quit_when_exporting_keeps_pending_changes() {
+        let quit = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        let mut state = AppState::new(vec![]);
+        state.export_on_quit = true;
+        make_change(&mut state, "A", Some("1".to_string()));
+        handle_list_mode(&mut state, quit);
+        assert!(state.should_quit);
+        assert_eq!(state.staged.len(), 1);
+    }
+
+    #[test]
+    fn test_session_changes_include_applied_to_process() {
+        let mut state = AppState::new(vec![("TEST_SESSION_KEPT".to_string(), "1".to_string())]);
+        make_change(&mut state, "TEST_SESSION_APPLIED", Some("1".to_string()));
+        make_change(&mut state, "TEST_SESSION_KEPT", Some("2".to_string()));
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('p'));
+        make_change(&mut state, "TEST_SESSION_KEPT", None);
+        make_change(&mut state, "TEST_SESSION_PENDING", Some("3".to_string()));
+        let session = state.session_changes();
+        let changes: Vec<(&str, Option<&str>)> = session
+            .iter()
+            .map(|c| (c.key.as_str(), c.new.as_deref()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("TEST_SESSION_APPLIED", Some("1")),
+                ("TEST_SESSION_KEPT", None),
+                ("TEST_SESSION_PENDING", Some("3")),
+            ]
+        );
+        unsafe { env::remove_var("TEST_SESSION_APPLIED") };
+        unsafe { env::remove_var("TEST_SESSION_KEPT") };
+    }
+
+    #[test]
+    fn test_session_changes_include_applied_globally() {
+        let mut state =
+            AppState::new(vec![("A".to_string(), "1".to_string())]).with_global_applier(|_| Ok(()));
+        make_change(&mut state, "A", Some("2".to_string()));
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('g'));
+        state.input_global = true;
+        finish_change(&mut state, "B", Some("3".to_string()));
+        let session = state.session_changes();
+        let changes: Vec<(&str, Option<&str>)> = session
+            .iter()
+            .map(|c| (c.key.as_str(), c.new.as_deref()))
+            .collect();
+        assert_eq!(changes, vec![("A", Some("2")), ("B", Some("3"))]);
+    }
+
+    #[test]
+    fn test_edit_dotenv_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "# app\nNAME=app\n# port\nPORT=80\nOLD=1\n").unwrap();
+        let mut state = AppState::new(dotenv::read_variables(&path).unwrap());
+        state.file = Some(path.clone());
+        make_change(&mut state, "PORT", Some("8080".to_string()));
+        make_change(&mut state, "OLD", None);
+        make_change(&mut state, "NEW", Some("two words".to_string()));
+        // Move PORT above NAME
+        state.select_position(1);
+        press(&mut state, KeyCode::Char('K'));
+        assert!(state.order_changed);
+        assert_eq!(state.entries[0].0, "PORT");
+        assert_eq!(state.current_index, 0);
+
+        // Review of file changes doesn't apply them to environment
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('p'));
+        assert_eq!(state.mode, Mode::Review);
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.mode, Mode::List);
+        assert!(!state.has_pending_changes());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# port\nPORT=8080\n# app\nNAME=app\nNEW='two words'\n"
+        );
+        assert_eq!(state.value_of("OLD"), None);
+    }
+
+    #[test]
+    fn test_move_entry_requires_file_order() {
+        let mut state = AppState::new(vec![
+            ("B".to_string(), "1".to_string()),
+            ("A".to_string(), "2".to_string()),
+        ]);
+        // Environment can't be reordered
+        press(&mut state, KeyCode::Char('J'));
+        assert!(!state.order_changed);
+
+        state.file = Some(".env".into());
+        press(&mut state, KeyCode::Char('s'));
+        press(&mut state, KeyCode::Char('J'));
+        assert!(!state.order_changed);
+        assert_eq!(
+            state.message,
+            Some("Switch to environment order to move variables".to_string())
+        );
+        press(&mut state, KeyCode::Char('s'));
+        press(&mut state, KeyCode::Char('s'));
+        press(&mut state, KeyCode::Char('s'));
+        press(&mut state, KeyCode::Char('J'));
+        assert_eq!(state.entries[1].0, "B");
+        // Unsaved order asks before quitting
+        let quit = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        handle_list_mode(&mut state, quit);
+        assert!(!state.should_quit);
+    }
+
+    #[test]
+    fn test_global_toggle_applies_right_away() {
+        let ctrl_g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL);
+        let mut state =
+            AppState::new(vec![("A".to_string(), "1".to_string())]).with_global_applier(|_| Ok(()));
+        make_change(&mut state, "A", Some("2".to_string()));
+
+        press(&mut state, KeyCode::Char('E'));
+        assert!(!state.input_global);
+        handle_edit_mode(&mut state, ctrl_g);
+        assert!(state.input_global);
+        handle_edit_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('3'), KeyModifiers::empty()),
+        );
+        handle_edit_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::empty()),
+        );
+        assert_eq!(state.message, Some("Variable updated globally".to_string()));
+        // Global change replaces pending one and its undo
+        assert!(state.staged.is_empty());
+        assert!(state.undo_stack.is_empty());
+        assert_eq!(state.value_of("A"), Some("23".to_string()));
+
+        press(&mut state, KeyCode::Char('a'));
+        assert!(!state.input_global);
+        handle_add_mode(&mut state, ctrl_g);
+        handle_add_mode(&mut state, ctrl_g);
+        assert!(!state.input_global);
+
+        press(&mut state, KeyCode::Char('d'));
+        handle_delete_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('g'), KeyModifiers::empty()),
+        );
+        handle_delete_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('y'), KeyModifiers::empty()),
+        );
+        assert_eq!(state.message, Some("Variable deleted globally".to_string()));
+        assert!(state.entries.is_empty());
+    }
+
+    #[test]
+    fn test_global_change_failure_keeps_pending_change() {
+        let mut state = AppState::new(vec![("A".to_string(), "1".to_string())])
+            .with_global_applier(|_| {
+                Err(ErrorKind::CannotSetVariableGlobally("denied".to_string()))
+            });
+        make_change(&mut state, "A", Some("2".to_string()));
+        state.input_global = true;
+        finish_change(&mut state, "A", Some("3".to_string()));
+        assert!(
+            state
+                .message
+                .as_ref()
+                .is_some_and(|message| message.starts_with("Failed to update variable:"))
+        );
+        assert_eq!(state.working_value("A"), Some("2".to_string()));
+        assert_eq!(state.undo_stack.len(), 1);
+    }
+
+    #[test]
+    fn test_add_rejects_invalid_input() {
+        let mut state = AppState::new(vec![]);
+        state.validation = ValidationProfile::Portable;
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::empty());
+        for (key, value) in [("A=B", "1"), ("lower", "1"), ("A", "a\0b")] {
+            press(&mut state, KeyCode::Char('a'));
+            state.input_key = key.to_string();
+            state.input_value = value.to_string();
+            handle_add_mode(&mut state, enter);
+            assert_eq!(state.mode, Mode::Add);
+            assert!(state.message.as_ref().is_some_and(|message| {
+                message.starts_with("Name validation error")
+                    || message.starts_with("Value validation error")
+            }));
+            handle_add_mode(
+                &mut state,
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::empty()),
+            );
+        }
+        assert!(state.staged.is_empty());
+
+        // Invalid input never reaches global environment either
+        state.global_applier = |_| panic!("invalid change applied globally");
+        state.input_global = true;
+        finish_change(&mut state, "A=B", Some("1".to_string()));
+        assert!(state.entries.is_empty());
+    }
+
+    #[test]
+    fn test_edit_input_with_multibyte_characters() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::empty());
+        let mut state = AppState::new(vec![("PATH".to_string(), "/bin".to_string())]);
+        press(&mut state, KeyCode::Char('e'));
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('a')));
+        for c in "/home/Jos/bin".chars() {
+            handle_list_edit_mode(&mut state, key(KeyCode::Char(c)));
+        }
+        for _ in 0..4 {
+            handle_list_edit_mode(&mut state, key(KeyCode::Left));
+        }
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('é')));
+        assert_eq!(state.input_value, "/home/José/bin");
+        assert_eq!(state.input_cursor_value, 10);
+        handle_list_edit_mode(&mut state, key(KeyCode::Enter));
+        assert_eq!(state.list_items, vec!["/bin", "/home/José/bin"]);
+
+        press(&mut state, KeyCode::Esc);
+        press(&mut state, KeyCode::Char('a'));
+        handle_add_mode(&mut state, key(KeyCode::Tab));
+        for c in "Jé!".chars() {
+            handle_add_mode(&mut state, key(KeyCode::Char(c)));
+        }
+        handle_add_mode(&mut state, key(KeyCode::Left));
+        handle_add_mode(&mut state, key(KeyCode::Char('x')));
+        assert_eq!(state.input_value, "Jéx!");
+        handle_add_mode(&mut state, key(KeyCode::Left));
+        handle_add_mode(&mut state, key(KeyCode::Backspace));
+        assert_eq!(state.input_value, "Jx!");
+        for _ in 0..5 {
+            handle_add_mode(&mut state, key(KeyCode::Right));
+        }
+        assert_eq!(state.input_cursor_value, 3);
+    }
+
+    #[test]
+    fn test_apply_staged_rejects_invalid_changes() {
+        let mut state = AppState::new(vec![])
+            .with_global_applier(|_| panic!("invalid change applied globally"));
+        state.staged.push(PlannedChange {
+            key: "A=B".to_string(),
+            old: None,
+            new: Some("1".to_string()),
+        });
+        press(&mut state, KeyCode::Char('w'));
+        press(&mut state, KeyCode::Char('g'));
+        press(&mut state, KeyCode::Char('p'));
+        assert_eq!(state.mode, Mode::Review);
+        assert!(
+            state
+                .message
+                .as_ref()
+                .is_some_and(|message| message.starts_with("Can't apply change of A=B:"))
+        );
+        assert_eq!(state.staged.len(), 1);
+    }
+
+    #[test]
+    fn test_global_toggle_ignored_for_dotenv_file() {
+        let mut state = AppState::new(vec![("A".to_string(), "1".to_string())]);
+        state.file = Some(".env".into());
+        press(&mut state, KeyCode::Char('d'));
+        handle_delete_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('g'), KeyModifiers::empty()),
+        );
+        assert!(!state.input_global);
+        handle_delete_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('y'), KeyModifiers::empty()),
+        );
+        assert_eq!(state.staged.len(), 1);
+    }
+
+    fn marked_state() -> AppState {
+        let mut state = AppState::new(vec![
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "2".to_string()),
+            ("C".to_string(), "3".to_string()),
+        ]);
+        press(&mut state, KeyCode::Char('b'));
+        assert_eq!(state.mode, Mode::List);
+        assert_eq!(
+            state.message,
+            Some("Mark variables with Space first".to_string())
+        );
+        // Mark A and C, Space moves to the next row
+        press(&mut state, KeyCode::Char(' '));
+        press(&mut state, KeyCode::Char(' '));
+        press(&mut state, KeyCode::Char(' '));
+        press(&mut state, KeyCode::Up);
+        press(&mut state, KeyCode::Char(' '));
+        assert_eq!(state.marked, vec!["A".to_string(), "C".to_string()]);
+        press(&mut state, KeyCode::Char('b'));
+        assert_eq!(state.mode, Mode::Bulk);
+        state
+    }
+
+    #[test]
+    fn test_bulk_delete() {
+        let mut state = marked_state();
+        press(&mut state, KeyCode::Char('d'));
+        assert_eq!(state.mode, Mode::BulkConfirm(BulkAction::Delete));
+        press(&mut state, KeyCode::Char('n'));
+        assert_eq!(state.mode, Mode::Bulk);
+        press(&mut state, KeyCode::Char('d'));
+        press(&mut state, KeyCode::Char('y'));
+        assert_eq!(state.mode, Mode::List);
+        assert!(state.marked.is_empty());
+        assert_eq!(state.working_value("A"), None);
+        assert_eq!(state.working_value("B"), Some("2".to_string()));
+        assert_eq!(state.working_value("C"), None);
+    }
+
+    #[test]
+    fn test_bulk_rename_with_prefix() {
+        let mut state = marked_state();
+        press(&mut state, KeyCode::Char('r'));
+        assert_eq!(state.mode, Mode::BulkRename);
+        for c in "APP_".chars() {
+            press(&mut state, KeyCode::Char(c));
+        }
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(
+            state.mode,
+            Mode::BulkConfirm(BulkAction::Rename("APP_".to_string()))
+        );
+        press(&mut state, KeyCode::Char('y'));
+        assert_eq!(state.working_value("A"), None);
+        assert_eq!(state.working_value("APP_A"), Some("1".to_string()));
+        assert_eq!(state.working_value("APP_C"), Some("3".to_string()));
+        assert_eq!(state.staged.len(), 4);
+
+        // Rename is undone at once
+        assert_eq!(state.undo_stack.len(), 1);
+        press(&mut state, KeyCode::Char('u'));
+        assert_eq!(
+            state.message,
+            Some("Undid change of 4 variables".to_string())
+        );
+        assert!(state.staged.is_empty());
+        assert_eq!(state.working_value("A"), Some("1".to_string()));
+    }
+
+    #[test]
+    fn test_bulk_rename_refuses_marked_target() {
+        let mut state = AppState::new(vec![
+            ("A".to_string(), "1".to_string()),
+            ("APP_A".to_string(), "9".to_string()),
+        ]);
+        state.marked = vec!["A".to_string(), "APP_A".to_string()];
+        state.mode = Mode::BulkRename;
+        state.input_value = "APP_".to_string();
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.mode, Mode::BulkRename);
+        assert_eq!(
+            state.message,
+            Some("Can't rename A to APP_A, it's marked too".to_string())
+        );
+        // Confirmed rename is refused as well
+        apply_bulk(&mut state, &BulkAction::Rename("APP_".to_string()));
+        assert!(state.staged.is_empty());
+        assert_eq!(state.working_value("A"), Some("1".to_string()));
+        assert_eq!(state.working_value("APP_A"), Some("9".to_string()));
+        assert_eq!(state.marked.len(), 2);
+    }
+
+    #[test]
+    fn test_bulk_rename_rejects_invalid_prefix() {
+        let mut state = AppState::new(vec![("A".to_string(), "1".to_string())]);
+        state.marked = vec!["A".to_string()];
+        state.mode = Mode::BulkRename;
+        state.input_value = "APP=".to_string();
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.mode, Mode::BulkRename);
+        assert_eq!(
+            state.message,
+            Some("Variable name cannot contain '='".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bulk_export_and_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exported.env");
+        let mut state = marked_state();
+        press(&mut state, KeyCode::Char('e'));
+        assert_eq!(state.mode, Mode::BulkExport);
+        state.input_value = path.display().to_string();
+        press(&mut state, KeyCode::Enter);
+        press(&mut state, KeyCode::Char('y'));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "A=1\nC=3\n");
+        assert!(state.staged.is_empty());
+
+        let mut state = marked_state();
+        press(&mut state, KeyCode::Char('v'));
+        press(&mut state, KeyCode::Char('y'));
+        assert_eq!(state.clipboard_requested, Some("A=1\nC=3".to_string()));
+        let mut state = marked_state();
+        press(&mut state, KeyCode::Char('c'));
+        press(&mut state, KeyCode::Char('y'));
+        assert_eq!(state.clipboard_requested, Some("A\nC".to_string()));
+        assert_eq!(
+            state.message,
+            Some("Copied names of 2 variable(s)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_quit_with_pending_changes_asks_again() {
+        let quit = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        let mut state = AppState::new(vec![]);
+        make_change(&mut state, "A", Some("1".to_string()));
+        handle_list_mode(&mut state, quit);
+        assert!(!state.should_quit);
+        assert!(
+            state
+                .message
+                .unwrap()
+                .starts_with("Pending changes will be lost")
+        );
+        state.message = None;
+        handle_list_mode(&mut state, quit);
+        assert!(state.should_quit);
+    }
+
+    #[test]
+    fn test_handle_list_mode_toggle_history() {
+        let mut state = AppState::new(vec![]);
+        press(&mut state, KeyCode::Char('h'));
+        assert!(state.show_history);
+        press(&mut state, KeyCode::Char('h'));
+        assert!(!state.show_history);
+    }
+
+    #[test]
+    fn test_search_filters_entries_live() {
+        let mut state = search_state();
+        press(&mut state, KeyCode::Char('/'));
+        assert_eq!(state.mode, Mode::Search);
+        press(&mut state, KeyCode::Char('s'));
+        press(&mut state, KeyCode::Char('h'));
+        // SHELL by name, PATH and USER by value
+        assert_eq!(state.visible_entries(), vec![1, 2]);
+        assert_eq!(state.current_index, 1);
+        press(&mut state, KeyCode::Backspace);
+        assert_eq!(state.visible_entries(), vec![1, 2, 3]);
+        press(&mut state, KeyCode::Enter);
+        assert_eq!(state.mode, Mode::List);
+        assert_eq!(state.filter, "s");
+    }
+
+    #[test]
+    fn test_search_jump_between_matches() {
+        let mut state = search_state();
+        state.filter = "sh".to_string();
+        state.current_index = 1;
+        press(&mut state, KeyCode::Char('n'));
+        assert_eq!(state.current_index, 2);
+        press(&mut state, KeyCode::Char('n'));
+        assert_eq!(state.current_index, 1);
+        press(&mut state, KeyCode::Char('N'));
+        assert_eq!(state.current_index, 2);
+    }
+
+    #[test]
+    fn test_search_esc_clears_filter_and_keeps_selection() {
+        let mut state = search_state();
+        state.mode = Mode::Search;
+        press(&mut state, KeyCode::Char('u'));
+        press(&mut state, KeyCode::Char('s'));
+        assert_eq!(state.current_index, 1);
+        press(&mut state, KeyCode::Down);
+        assert_eq!(state.current_index, 3);
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.mode, Mode::List);
+        assert!(state.filter.is_empty());
+        assert_eq!(state.current_index, 3);
+    }
+
+    #[test]
+    fn test_search_without_matches() {
+        let mut state = search_state();
+        state.mode = Mode::Search;
+        press(&mut state, KeyCode::Char('x'));
+        assert!(state.visible_entries().is_empty());
+        assert!(state.selected_entry().is_none());
+        press(&mut state, KeyCode::Enter);
+        press(&mut state, KeyCode::Char('e'));
+        assert_eq!(state.mode, Mode::List);
+    }
+
+    #[test]
+    fn test_search_skips_masked_values() {
+        let mut state = AppState::new(vec![("API_TOKEN".to_string(), "hunter2".to_string())]);
+        state.filter = "hunter".to_string();
+        assert!(state.visible_entries().is_empty());
+        state.reveal_secrets = true;
+        assert_eq!(state.visible_entries(), vec![0]);
+    }
+
+    fn list_edit_state() -> AppState {
+        let mut state = AppState::new(vec![("PATH".to_string(), "/a:/b".to_string())]);
+        state.mode = Mode::ListEdit("PATH".to_string());
+        state.list_items = vec!["/a".to_string(), "/b".to_string(), "/c".to_string()];
+        state
+    }
+
+    #[test]
+    fn test_handle_list_mode_edit_path_list() {
+        let path = env::join_paths(["/usr/bin", "/bin"]).unwrap();
+        let mut state = AppState::new(vec![(
+            "PATH".to_string(),
+            path.to_string_lossy().to_string(),
+        )]);
+        handle_list_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('e'), KeyModifiers::empty()),
+        );
+        assert_eq!(state.mode, Mode::ListEdit("PATH".to_string()));
+        assert_eq!(state.list_items, vec!["/usr/bin", "/bin"]);
+
+        state.mode = Mode::List;
+        handle_list_mode(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('E'), KeyModifiers::SHIFT),
+        );
+        assert_eq!(state.mode, Mode::Edit("PATH".to_string()));
+    }
+
+    #[test]
+    fn test_handle_list_edit_mode_reorder_and_remove() {
+        let mut state = list_edit_state();
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        handle_list_edit_mode(&mut state, key(KeyCode::Down, KeyModifiers::empty()));
+        assert_eq!(state.list_index, 1);
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('K'), KeyModifiers::SHIFT));
+        assert_eq!(state.list_items, vec!["/b", "/a", "/c"]);
+        assert_eq!(state.list_index, 0);
+        handle_list_edit_mode(&mut state, key(KeyCode::Down, KeyModifiers::SHIFT));
+        assert_eq!(state.list_items, vec!["/a", "/b", "/c"]);
+        assert_eq!(state.list_index, 1);
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('d'), KeyModifiers::empty()));
+        assert_eq!(state.list_items, vec!["/a", "/c"]);
+        assert_eq!(state.list_index, 1);
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('d'), KeyModifiers::empty()));
+        assert_eq!(state.list_index, 0);
+    }
+
+    #[test]
+    fn test_handle_list_edit_mode_add() {
+        let mut state = list_edit_state();
+        let key = |code| KeyEvent::new(code, KeyModifiers::empty());
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('a')));
+        assert!(state.list_adding);
+        for c in "/new".chars() {
+            handle_list_edit_mode(&mut state, key(KeyCode::Char(c)));
+        }
+        handle_list_edit_mode(&mut state, key(KeyCode::Enter));
+        assert!(!state.list_adding);
+        assert_eq!(state.list_items, vec!["/a", "/new", "/b", "/c"]);
+        assert_eq!(state.list_index, 1);
+        assert_eq!(state.mode, Mode::ListEdit("PATH".to_string()));
+
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('a')));
+        handle_list_edit_mode(&mut state, key(KeyCode::Char('x')));
+        handle_list_edit_mode(&mut state, key(KeyCode::Esc));
+        assert_eq!(state.list_items.len(), 4);
+        handle_list_edit_mode(&mut state, key(KeyCode::Esc));
+        assert_eq!(state.mode, Mode::List);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_handle_list_edit_mode_enter_invalid_entry() {
+        let mut state = list_edit_state();
+        state.list_items.push("/d:/e".to_string());
+        let key_event = KeyEvent::new(KeyCode::Enter, KeyModifiers::empty());
+        handle_list_edit_mode(&mut state, key_event);
+        assert_eq!(state.mode, Mode::ListEdit("PATH".to_string()));
+        assert!(state.message.unwrap().starts_with("Invalid path entry"));
+        assert_eq!(state.entries[0].1, "/a:/b");
+    }
+
     #[test]
     fn test_handle_add_mode_enter() {
         let mut state = AppState::new(vec![]);
@@ -0,0 +1,31 @@
+/// Find characters of query in text in the same order ignoring case.
+///
+/// Returns positions of matched characters in text, empty query matches everything.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<Vec<usize>> {
+    let mut positions = vec![];
+    let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+    for (position, c) in text.chars().enumerate() {
+        let Some(expected) = query.peek() else {
+            break;
+        };
+        if c.to_lowercase().any(|c| c == *expected) {
+            positions.push(position);
+            query.next();
+        }
+    }
+    query.peek().is_none().then_some(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("pth", "PATH"), Some(vec![0, 2, 3]));
+        assert_eq!(fuzzy_match("", "PATH"), Some(vec![]));
+        assert_eq!(fuzzy_match("home", "HOME"), Some(vec![0, 1, 2, 3]));
+        assert_eq!(fuzzy_match("hp", "PATH"), None);
+        assert_eq!(fuzzy_match("path", "PAT"), None);
+    }
+}
@@ -1,11 +1,42 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+use crate::config::get_history_file_path;
+use crate::interactive::search::fuzzy_match;
+use crate::models::{ErrorKind, ValidationProfile};
+use crate::secrets::SecretFilter;
+use crate::variables::{self, PlannedChange};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Mode {
     List,
+    Search,
     Add,
-    Edit(String),   // Holds the key being edited.
-    Delete(String), // Holds the key to be deleted.
+    Edit(String),            // Holds the key being edited.
+    ListEdit(String),        // Holds the key of path list being edited.
+    Delete(String),          // Holds the key to be deleted.
+    Review,                  // Shows pending changes before applying them.
+    ApplyFile,               // Asks for dotenv file pending changes are written to.
+    SaveFile,                // Asks for dotenv file the whole environment is saved to.
+    Run,                     // Asks for command run with pending changes.
+    Bulk,                    // Lists operations for marked variables.
+    BulkExport,              // Asks for dotenv file marked variables are exported to.
+    BulkRename,              // Asks for prefix added to names of marked variables.
+    BulkConfirm(BulkAction), // Holds operation waiting for confirmation.
+}
+
+/// Operation applied to all marked variables at once.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction {
+    Delete,
+    /// Holds dotenv file variables are written to.
+    Export(String),
+    CopyNames,
+    /// Copies variables as NAME=value lines.
+    CopyValues,
+    /// Holds prefix added to names.
+    Rename(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +45,64 @@ pub enum InputFocus {
     Value,
 }
 
+/// Order entries are listed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Order in which variables were read from environment.
+    #[default]
+    Environment,
+    Name,
+    /// Shortest values first.
+    ValueLength,
+    /// Variables changed during the session first, most recent at the top.
+    Modified,
+}
+
+impl SortOrder {
+    /// Get order that follows this one when toggling.
+    pub fn next(self) -> Self {
+        match self {
+            SortOrder::Environment => SortOrder::Name,
+            SortOrder::Name => SortOrder::ValueLength,
+            SortOrder::ValueLength => SortOrder::Modified,
+            SortOrder::Modified => SortOrder::Environment,
+        }
+    }
+
+    /// Get description shown in the list title.
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Environment => "environment order",
+            SortOrder::Name => "by name",
+            SortOrder::ValueLength => "by value length",
+            SortOrder::Modified => "recently modified",
+        }
+    }
+}
+
+/// Positions of characters matched by the filter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntryMatch {
+    pub name: Vec<usize>,
+    pub value: Vec<usize>,
+}
+
+/// Get scroll offset that shows given row of list with `len` entries drawn in `rows` rows.
+///
+/// Offset changes only as much as needed, and rows at the bottom are never left empty
+/// while entries above them are scrolled out.
+pub fn scroll_offset(offset: usize, position: usize, len: usize, rows: usize) -> usize {
+    let rows = rows.max(1);
+    let offset = if position < offset {
+        position
+    } else if position >= offset + rows {
+        position + 1 - rows
+    } else {
+        offset
+    };
+    offset.min(len.saturating_sub(rows))
+}
+
 #[cfg(test)]
 pub type VariableGetter = Option<Box<dyn Fn() -> Vec<(String, String)>>>;
 
@@ -23,6 +112,8 @@ pub struct AppState {
     pub entries: Vec<(String, String)>,
     pub current_index: usize,
     pub scroll_offset: usize,
+    // Number of rows the list was last drawn with, zero before it's drawn.
+    pub list_height: usize,
     pub message: Option<String>,
     pub message_expiry: Option<Instant>,
     // Input buffers for add/edit modes.
@@ -33,8 +124,56 @@ pub struct AppState {
     pub input_cursor_value: usize,
     // Which field is currently focused.
     pub input_focus: InputFocus,
+    // Whether add, edit or delete is applied globally right away instead of staged.
+    pub input_global: bool,
     // Flag to indicate a reload request.
     pub reload_requested: bool,
+    // Decides which values are masked in the list.
+    pub secrets: SecretFilter,
+    // Rules names of added and changed variables are checked with.
+    pub validation: ValidationProfile,
+    // Show secret values instead of mask.
+    pub reveal_secrets: bool,
+    // Query entries are filtered by, empty shows all entries.
+    pub filter: String,
+    // Order of the list and keys changed during the session, most recent last.
+    pub sort_order: SortOrder,
+    pub modified: Vec<String>,
+    // Whether next typed character selects variable starting with it.
+    pub jump_pending: bool,
+    // Changes made during the session that can be undone, most recent last.
+    // Changes made by one operation, like bulk rename, are grouped and undone together.
+    pub undo_stack: Vec<Vec<PlannedChange>>,
+    // Undone changes that can be made again, most recently undone last.
+    pub redo_stack: Vec<Vec<PlannedChange>>,
+    // Show history of changes instead of details of selected variable.
+    pub show_history: bool,
+    // Entries of path list in list edit mode.
+    pub list_items: Vec<String>,
+    pub list_index: usize,
+    pub list_scroll_offset: usize,
+    // Whether new path list entry is typed into input_value.
+    pub list_adding: bool,
+    // Pending changes, old holds value from before the session and new the working value.
+    pub staged: Vec<PlannedChange>,
+    // Changes applied to process or global environment during the session, most recent last.
+    pub applied: Vec<PlannedChange>,
+    // Whether quitting with pending changes was already warned about.
+    pub confirm_quit: bool,
+    // Pending changes are printed as export statements on quit, so quitting doesn't lose them.
+    pub export_on_quit: bool,
+    // Command to run with pending changes once the interface is suspended.
+    pub run_requested: Option<String>,
+    // Text to copy to clipboard through the terminal.
+    pub clipboard_requested: Option<String>,
+    // Names of variables marked for bulk operations, in order they were marked.
+    pub marked: Vec<String>,
+    // Dotenv file edited instead of environment.
+    pub file: Option<PathBuf>,
+    // Whether variables of the file were reordered since it was saved.
+    pub order_changed: bool,
+    // Applies change to global environment, tests replace it to never touch global environment.
+    pub global_applier: fn(&PlannedChange) -> Result<(), ErrorKind>,
     // Optional variable getter override for testing.
     #[cfg(test)]
     pub variable_getter: VariableGetter,
@@ -48,6 +187,7 @@ impl AppState {
             entries,
             current_index: 0,
             scroll_offset: 0,
+            list_height: 0,
             message: None,
             message_expiry: None,
             input_key: String::new(),
@@ -55,12 +195,49 @@ impl AppState {
             input_cursor_key: 0,
             input_cursor_value: 0,
             input_focus: InputFocus::Key,
+            input_global: false,
             reload_requested: false,
+            secrets: SecretFilter::default(),
+            validation: ValidationProfile::default(),
+            reveal_secrets: false,
+            filter: String::new(),
+            sort_order: SortOrder::default(),
+            modified: vec![],
+            jump_pending: false,
+            undo_stack: vec![],
+            redo_stack: vec![],
+            show_history: false,
+            list_items: vec![],
+            list_index: 0,
+            list_scroll_offset: 0,
+            list_adding: false,
+            staged: vec![],
+            applied: vec![],
+            confirm_quit: false,
+            export_on_quit: false,
+            run_requested: None,
+            clipboard_requested: None,
+            marked: vec![],
+            file: None,
+            order_changed: false,
+            global_applier: |change| {
+                variables::apply_change(change, true, &get_history_file_path())
+            },
             #[cfg(test)]
             variable_getter: None,
         }
     }
 
+    /// Apply global changes with given function instead of changing global environment.
+    #[cfg(test)]
+    pub fn with_global_applier(
+        mut self,
+        applier: fn(&PlannedChange) -> Result<(), ErrorKind>,
+    ) -> Self {
+        self.global_applier = applier;
+        self
+    }
+
     /// Show a temporary message.
     pub fn show_message(&mut self, msg: &str, duration: Duration) {
         self.message = Some(msg.to_string());
@@ -73,6 +250,311 @@ impl AppState {
         self.message_expiry = None;
     }
 
+    /// Get value as shown in the list, masking secrets unless they're revealed.
+    pub fn display_value<'a>(&self, key: &str, value: &'a str) -> Cow<'a, str> {
+        if self.reveal_secrets {
+            Cow::Borrowed(value)
+        } else {
+            self.secrets.mask(key, value)
+        }
+    }
+
+    /// Match the filter against entry name, then against its shown value.
+    pub fn entry_match(&self, key: &str, value: &str) -> Option<EntryMatch> {
+        if let Some(name) = fuzzy_match(&self.filter, key) {
+            return Some(EntryMatch {
+                name,
+                value: vec![],
+            });
+        }
+        fuzzy_match(&self.filter, &self.display_value(key, value)).map(|value| EntryMatch {
+            name: vec![],
+            value,
+        })
+    }
+
+    /// Indices of entries that match the filter, in sort order.
+    pub fn visible_entries(&self) -> Vec<usize> {
+        let mut visible: Vec<usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (k, v))| self.entry_match(k, v).is_some())
+            .map(|(i, _)| i)
+            .collect();
+        let entries = &self.entries;
+        match self.sort_order {
+            SortOrder::Environment => {}
+            SortOrder::Name => visible.sort_by(|a, b| entries[*a].0.cmp(&entries[*b].0)),
+            SortOrder::ValueLength => visible.sort_by(|a, b| {
+                let length = |i: usize| entries[i].1.chars().count();
+                length(*a)
+                    .cmp(&length(*b))
+                    .then_with(|| entries[*a].0.cmp(&entries[*b].0))
+            }),
+            SortOrder::Modified => visible.sort_by(|a, b| {
+                let recency = |i: usize| self.modified.iter().position(|k| *k == entries[i].0);
+                recency(*b)
+                    .cmp(&recency(*a))
+                    .then_with(|| entries[*a].0.cmp(&entries[*b].0))
+            }),
+        }
+        visible
+    }
+
+    /// Get value of variable in the list.
+    pub fn value_of(&self, key: &str) -> Option<String> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+    }
+
+    /// Set value of variable in the list, removing it if value is missing.
+    pub fn set_entry(&mut self, key: &str, value: Option<String>) {
+        let index = self.entries.iter().position(|(k, _)| k == key);
+        match (index, value) {
+            (Some(index), Some(value)) => self.entries[index].1 = value,
+            (None, Some(value)) => self.entries.push((key.to_string(), value)),
+            (Some(index), None) => {
+                self.entries.remove(index);
+                self.current_index = self.current_index.min(self.entries.len().saturating_sub(1));
+            }
+            (None, None) => {}
+        }
+    }
+
+    /// Get pending change of variable.
+    pub fn staged_change(&self, key: &str) -> Option<&PlannedChange> {
+        self.staged.iter().find(|c| c.key == key)
+    }
+
+    /// Get value variable will have once pending changes are applied.
+    pub fn working_value(&self, key: &str) -> Option<String> {
+        match self.staged_change(key) {
+            Some(change) => change.new.clone(),
+            None => self.value_of(key),
+        }
+    }
+
+    /// Whether there are changes that weren't applied or saved yet.
+    pub fn has_pending_changes(&self) -> bool {
+        !self.staged.is_empty() || self.order_changed
+    }
+
+    /// Variables with pending changes applied, leaving out deleted ones.
+    pub fn working_entries(&self) -> Vec<(String, String)> {
+        self.entries
+            .iter()
+            .filter_map(|(k, _)| self.working_value(k).map(|v| (k.clone(), v)))
+            .collect()
+    }
+
+    /// Remember change applied to process or global environment to export it on quit.
+    ///
+    /// Shell that started the session doesn't see either of them until it's exported.
+    pub fn record_applied(&mut self, change: &PlannedChange) {
+        self.applied.retain(|c| c.key != change.key);
+        self.applied.push(change.clone());
+    }
+
+    /// Changes made during the session to print on quit, applied and pending ones, latest per variable.
+    pub fn session_changes(&self) -> Vec<PlannedChange> {
+        let mut changes = self.applied.clone();
+        for change in &self.staged {
+            changes.retain(|c| c.key != change.key);
+            changes.push(change.clone());
+        }
+        changes
+    }
+
+    /// Mark selected variable for bulk operations, or unmark it if it's marked.
+    pub fn toggle_mark(&mut self) {
+        let Some((key, _)) = self.selected_entry() else {
+            return;
+        };
+        let key = key.clone();
+        if self.marked.contains(&key) {
+            self.marked.retain(|k| *k != key);
+        } else {
+            self.marked.push(key);
+        }
+    }
+
+    /// Marked variables that aren't deleted, in order they are listed in.
+    pub fn marked_entries(&self) -> Vec<(String, String)> {
+        self.working_entries()
+            .into_iter()
+            .filter(|(k, _)| self.marked.contains(k))
+            .collect()
+    }
+
+    /// Stage new value of variable, or its deletion if value is missing.
+    ///
+    /// Deleted variables stay in the list with their original value until
+    /// changes are applied, changes back to the original value are unstaged.
+    pub fn stage(&mut self, key: &str, value: Option<String>) {
+        let original = match self.staged_change(key) {
+            Some(change) => change.old.clone(),
+            None => self.value_of(key),
+        };
+        self.staged.retain(|c| c.key != key);
+        if original != value {
+            self.staged.push(PlannedChange {
+                key: key.to_string(),
+                old: original.clone(),
+                new: value.clone(),
+            });
+        }
+        self.set_entry(key, value.or(original));
+    }
+
+    /// Drop all pending changes, restoring original values.
+    pub fn discard_staged(&mut self) {
+        for change in std::mem::take(&mut self.staged) {
+            self.set_entry(&change.key, change.old);
+        }
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        // Original order can only be read from the file again
+        if self.order_changed {
+            self.order_changed = false;
+            self.reload();
+        }
+        self.move_selection(0, false);
+    }
+
+    /// Forget pending changes after they were applied, removing deleted variables.
+    pub fn finish_apply(&mut self) {
+        for change in self.staged.clone() {
+            self.finish_apply_of(&change.key);
+        }
+        self.move_selection(0, false);
+    }
+
+    /// Forget pending change of variable after it was applied, removing variable if it was deleted.
+    pub fn finish_apply_of(&mut self, key: &str) {
+        if let Some(index) = self.staged.iter().position(|c| c.key == key) {
+            let change = self.staged.remove(index);
+            if change.new.is_none() {
+                self.set_entry(key, None);
+            }
+        }
+    }
+
+    /// Remember that variable was changed for sorting by modification.
+    pub fn mark_modified(&mut self, key: &str) {
+        self.modified.retain(|k| k != key);
+        self.modified.push(key.to_string());
+    }
+
+    /// Swap selected entry with the next or previous visible one.
+    pub fn move_entry(&mut self, down: bool) {
+        let visible = self.visible_entries();
+        let Some(position) = visible.iter().position(|i| *i == self.current_index) else {
+            return;
+        };
+        let target = if down {
+            position + 1
+        } else if let Some(previous) = position.checked_sub(1) {
+            previous
+        } else {
+            return;
+        };
+        if let Some(&index) = visible.get(target) {
+            self.entries.swap(self.current_index, index);
+            self.order_changed = true;
+            self.select_position(target);
+        }
+    }
+
+    /// Switch to next sort order, keeping selected entry.
+    pub fn toggle_sort(&mut self) {
+        self.sort_order = self.sort_order.next();
+        self.move_selection(0, false);
+    }
+
+    /// Get selected entry if it's visible.
+    pub fn selected_entry(&self) -> Option<&(String, String)> {
+        self.entries
+            .get(self.current_index)
+            .filter(|(k, v)| self.entry_match(k, v).is_some())
+    }
+
+    /// Move selection by given number of visible entries, optionally wrapping around.
+    ///
+    /// If selected entry is hidden by the filter, the first visible entry is selected.
+    pub fn move_selection(&mut self, step: isize, wrap: bool) {
+        let visible = self.visible_entries();
+        if visible.is_empty() {
+            return;
+        }
+        let len = visible.len() as isize;
+        let position = match visible.iter().position(|i| *i == self.current_index) {
+            Some(position) if wrap => (position as isize + step).rem_euclid(len) as usize,
+            Some(position) => (position as isize).saturating_add(step).clamp(0, len - 1) as usize,
+            None => 0,
+        };
+        self.select_position(position);
+    }
+
+    /// Select visible entry at given row of the list, the last one if row is out of range.
+    pub fn select_position(&mut self, position: usize) {
+        let visible = self.visible_entries();
+        if let Some(last) = visible.len().checked_sub(1) {
+            let position = position.min(last);
+            self.current_index = visible[position];
+            self.scroll_to(position, visible.len());
+        }
+    }
+
+    /// Select next visible variable whose name starts with given character, ignoring case.
+    pub fn jump_to(&mut self, c: char) {
+        let visible = self.visible_entries();
+        let current = visible
+            .iter()
+            .position(|i| *i == self.current_index)
+            .unwrap_or(visible.len());
+        let starts_with = |i: usize| {
+            self.entries[i]
+                .0
+                .chars()
+                .next()
+                .is_some_and(|first| first.to_lowercase().eq(c.to_lowercase()))
+        };
+        // Search after the current row first, then wrap around to the beginning.
+        let found = (1..=visible.len())
+            .map(|offset| (current + offset) % visible.len())
+            .find(|position| starts_with(visible[*position]));
+        if let Some(position) = found {
+            self.select_position(position);
+        }
+    }
+
+    /// Clear the filter, keeping selected entry.
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.move_selection(0, false);
+    }
+
+    /// Number of entries shown in the list at once, as it was last drawn.
+    pub fn page_size(&self) -> usize {
+        self.list_height.max(1)
+    }
+
+    /// Remember number of rows the list is drawn with, keeping selected entry in view.
+    pub fn set_list_height(&mut self, height: usize) {
+        self.list_height = height;
+        let visible = self.visible_entries();
+        let position = visible.iter().position(|i| *i == self.current_index);
+        self.scroll_to(position.unwrap_or(0), visible.len());
+    }
+
+    /// Adjust scroll offset so that given row of the list with given number of rows is shown.
+    fn scroll_to(&mut self, position: usize, len: usize) {
+        self.scroll_offset = scroll_offset(self.scroll_offset, position, len, self.page_size());
+    }
+
     /// Request a reload of the variable list.
     pub fn request_reload(&mut self) {
         self.reload_requested = true;
@@ -80,23 +562,44 @@ impl AppState {
 
     /// Reload the list of variables.
     ///
-    /// In production this calls `crate::variables::get_variables()`,
-    /// but if `variable_getter` is set (e.g. in tests) it will use that.
+    /// In production this calls `crate::variables::get_variables()`, or reads
+    /// the edited dotenv file, but if `variable_getter` is set (e.g. in tests)
+    /// it will use that.
     pub fn reload(&mut self) {
-        #[cfg(test)]
-        {
-            if let Some(ref getter) = self.variable_getter {
-                self.entries = getter();
-            } else {
+        if let Some(file) = &self.file {
+            match crate::dotenv::read_variables(file) {
+                Ok(variables) => self.entries = variables,
+                Err(err) => {
+                    self.reload_requested = false;
+                    self.show_message(
+                        &format!("Failed to read file: {}", err),
+                        Duration::from_secs(3),
+                    );
+                    return;
+                }
+            }
+        } else {
+            #[cfg(test)]
+            {
+                if let Some(ref getter) = self.variable_getter {
+                    self.entries = getter();
+                } else {
+                    self.entries = crate::variables::get_variables();
+                }
+            }
+            #[cfg(not(test))]
+            {
                 self.entries = crate::variables::get_variables();
             }
         }
-        #[cfg(not(test))]
-        {
-            self.entries = crate::variables::get_variables();
+        self.order_changed = false;
+        // Keep pending changes shown on top of freshly read values
+        for change in self.staged.clone() {
+            self.set_entry(&change.key, change.new.or(change.old));
         }
         self.current_index = 0;
         self.scroll_offset = 0;
+        self.select_position(0);
         self.reload_requested = false;
         self.show_message("List reloaded", Duration::from_secs(2));
     }
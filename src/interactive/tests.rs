@@ -4,8 +4,34 @@ use crate::interactive::{
     InteractiveApp,
     state::{AppState, InputFocus, Mode},
 };
+use crate::models::InteractiveArgs;
+use crate::secrets::SecretFilter;
 use std::time::Duration;
 
+#[test]
+fn test_interactive_app_reads_dotenv_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(".env");
+    std::fs::write(&path, "B=1\nA=2\n").unwrap();
+    let args = InteractiveArgs {
+        file: Some(path.display().to_string()),
+        export: None,
+        validation: None,
+    };
+    let app = InteractiveApp::new(SecretFilter::default(), &args).unwrap();
+    assert_eq!(
+        app.state.entries,
+        vec![
+            ("B".to_string(), "1".to_string()),
+            ("A".to_string(), "2".to_string())
+        ]
+    );
+    assert_eq!(app.state.file, Some(path.clone()));
+
+    std::fs::write(&path, "TEST_VAR test_value\n").unwrap();
+    assert!(InteractiveApp::new(SecretFilter::default(), &args).is_err());
+}
+
 #[test]
 fn test_show_and_clear_message() {
     let mut state = AppState::new(vec![]);
@@ -51,11 +77,11 @@ fn test_edit_variable() {
     let mut state = AppState::new(vec![("VAR1".to_string(), "OLD".to_string())]);
     state.mode = Mode::Edit("VAR1".to_string());
     state.input_value = "NEW".to_string();
-    if let Mode::Edit(ref key) = state.mode {
-        if let Some(entry) = state.entries.iter_mut().find(|(k, _)| k == key) {
-            entry.1 = state.input_value.trim().to_string();
-            state.mode = Mode::List;
-        }
+    if let Mode::Edit(ref key) = state.mode
+        && let Some(entry) = state.entries.iter_mut().find(|(k, _)| k == key)
+    {
+        entry.1 = state.input_value.trim().to_string();
+        state.mode = Mode::List;
     }
     assert_eq!(state.entries[0], ("VAR1".to_string(), "NEW".to_string()));
 }
@@ -92,14 +118,30 @@ fn test_reload() {
 
 #[test]
 fn test_interactive_app_creation() {
-    let app = InteractiveApp::new();
+    let app = InteractiveApp::new(
+        SecretFilter::default(),
+        &InteractiveArgs {
+            file: None,
+            export: None,
+            validation: None,
+        },
+    )
+    .unwrap();
     assert!(!app.state.should_quit);
     assert!(!app.state.entries.is_empty());
 }
 
 #[test]
 fn test_app_quit_state() {
-    let mut app = InteractiveApp::new();
+    let mut app = InteractiveApp::new(
+        SecretFilter::default(),
+        &InteractiveArgs {
+            file: None,
+            export: None,
+            validation: None,
+        },
+    )
+    .unwrap();
     app.state.should_quit = true;
 
     let backend = TestBackend::new(20, 20);
@@ -118,6 +160,21 @@ fn test_state_initialization() {
     assert!(!state.should_quit);
 }
 
+#[test]
+fn test_scroll_offset() {
+    use crate::interactive::state::scroll_offset;
+    // Selection inside the shown rows keeps offset
+    assert_eq!(scroll_offset(5, 8, 30, 10), 5);
+    // Moving above or below scrolls only as far as needed
+    assert_eq!(scroll_offset(5, 2, 30, 10), 2);
+    assert_eq!(scroll_offset(5, 20, 30, 10), 11);
+    // Offset never leaves rows empty at the bottom
+    assert_eq!(scroll_offset(25, 27, 30, 10), 20);
+    assert_eq!(scroll_offset(3, 4, 5, 10), 0);
+    // List that wasn't drawn yet shows at least one row
+    assert_eq!(scroll_offset(0, 3, 5, 0), 3);
+}
+
 #[test]
 fn test_handle_list_mode_quit() {
     use crate::interactive::controller::handle_list_mode;
@@ -204,11 +261,24 @@ fn test_handle_list_mode_reload() {
     use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
     let mut state = AppState::new(vec![]);
-    let key_event = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+    let key_event = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL);
     handle_list_mode(&mut state, key_event);
     assert!(state.reload_requested);
 }
 
+#[test]
+fn test_handle_list_mode_toggle_reveal() {
+    use crate::interactive::controller::handle_list_mode;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    let mut state = AppState::new(vec![]);
+    let key_event = KeyEvent::new(KeyCode::Char('v'), KeyModifiers::empty());
+    handle_list_mode(&mut state, key_event);
+    assert!(state.reveal_secrets);
+    handle_list_mode(&mut state, key_event);
+    assert!(!state.reveal_secrets);
+}
+
 #[test]
 fn test_handle_add_mode_enter() {
     use crate::interactive::controller::handle_add_mode;
@@ -1,11 +1,16 @@
-use crate::interactive::state::{AppState, InputFocus, Mode};
+use crate::interactive::state::{AppState, BulkAction, InputFocus, Mode, scroll_offset};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use std::env;
+use std::path::Path;
 
-pub fn render(state: &AppState, f: &mut Frame) {
+use crate::utils::is_path_list;
+use crate::variables::PlannedChange;
+
+pub fn render(state: &mut AppState, f: &mut Frame) {
     let size = f.area();
     // Divide the screen: main area and footer.
     let chunks = Layout::default()
@@ -14,36 +19,119 @@ pub fn render(state: &AppState, f: &mut Frame) {
         .split(size);
 
     match &state.mode {
-        Mode::List => {
-            let items: Vec<ListItem> = state
-                .entries
+        Mode::List | Mode::Search => {
+            // List of variables on the left and full value of selected one on the right.
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+                .split(chunks[0]);
+            // Borders take one row at the top and one at the bottom
+            state.set_list_height(panes[0].height.saturating_sub(2) as usize);
+            let state = &*state;
+            let visible = state.visible_entries();
+            let items: Vec<ListItem> = visible
                 .iter()
-                .enumerate()
-                .map(|(i, (k, v))| {
-                    let marker = if i == state.current_index { "> " } else { "  " };
+                .map(|&i| {
+                    let (k, v) = &state.entries[i];
+                    // Cursor followed by mark of variables selected for bulk operations
+                    let marker = format!(
+                        "{}{}",
+                        if i == state.current_index { '>' } else { ' ' },
+                        if state.marked.contains(k) { '*' } else { ' ' }
+                    );
                     let key_field = format!("{:30}", k);
-                    let content = format!("{}{}  {}", marker, key_field, v);
-                    let style = if i == state.current_index {
+                    // Keep multi-line values on one row, replacing each character to keep match positions
+                    let value: String = state
+                        .display_value(k, v)
+                        .chars()
+                        .map(|c| match c {
+                            '\n' | '\r' => '↵',
+                            '\t' => ' ',
+                            c => c,
+                        })
+                        .collect();
+                    let mut style = if i == state.current_index {
                         Style::default()
                             .fg(Color::Green)
                             .add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(Color::White)
                     };
-                    ListItem::new(Line::from(Span::styled(content, style)))
+                    let (status, status_color) = match state.staged_change(k) {
+                        Some(PlannedChange { old: None, .. }) => ("+ ", Color::Green),
+                        Some(PlannedChange { new: None, .. }) => {
+                            style = style.add_modifier(Modifier::CROSSED_OUT);
+                            ("- ", Color::Red)
+                        }
+                        Some(_) => ("~ ", Color::Yellow),
+                        None => ("  ", Color::White),
+                    };
+                    let matched = state.entry_match(k, v).unwrap_or_default();
+                    let mut spans = vec![
+                        Span::styled(marker, style),
+                        Span::styled(status, Style::default().fg(status_color)),
+                    ];
+                    spans.extend(highlight(&key_field, &matched.name, style));
+                    spans.push(Span::styled("  ", style));
+                    spans.extend(highlight(&value, &matched.value, style));
+                    ListItem::new(Line::from(spans))
                 })
                 .collect();
 
+            // Edited dotenv file is named instead of environment
+            let source = match &state.file {
+                Some(file) => file.display().to_string(),
+                None => "Variables".to_string(),
+            };
+            let mut title = if state.filter.is_empty() {
+                format!("{} ({}", source, state.sort_order.label())
+            } else {
+                format!(
+                    "{} ({}, {} of {} match \"{}\"",
+                    source,
+                    state.sort_order.label(),
+                    visible.len(),
+                    state.entries.len(),
+                    state.filter
+                )
+            };
+            if !state.staged.is_empty() {
+                title.push_str(&format!(", {} pending", state.staged.len()));
+            }
+            if state.order_changed {
+                title.push_str(", reordered");
+            }
+            if !state.marked.is_empty() {
+                title.push_str(&format!(", {} marked", state.marked.len()));
+            }
+            title.push(')');
             let list = List::new(items).block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
                     .border_style(Style::default().fg(Color::Blue))
-                    .title("Variables"),
+                    .title(title),
             );
-            let mut list_state = ListState::default();
-            list_state.select(Some(state.current_index));
-            f.render_stateful_widget(list, chunks[0], &mut list_state);
+            let mut list_state = ListState::default()
+                .with_offset(state.scroll_offset)
+                .with_selected(visible.iter().position(|i| *i == state.current_index));
+            f.render_stateful_widget(list, panes[0], &mut list_state);
+
+            let (title, lines) = match state.selected_entry() {
+                _ if state.show_history => ("History".to_string(), history_lines(state)),
+                Some((k, v)) => (k.clone(), detail_lines(k, &state.display_value(k, v))),
+                None => ("Details".to_string(), vec![]),
+            };
+            let detail = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Blue))
+                        .title(title),
+                )
+                .wrap(Wrap { trim: false });
+            f.render_widget(detail, panes[1]);
         }
         Mode::Add => {
             let modal = Paragraph::new(vec![
@@ -55,6 +143,7 @@ pub fn render(state: &AppState, f: &mut Frame) {
                 )),
                 Line::from(format!("Key: {}", state.input_key)),
                 Line::from(format!("Value: {}", state.input_value)),
+                global_line(state, "Ctrl+g"),
                 Line::from("Enter=confirm, Esc=cancel, Tab=switch field, ←/→ move cursor"),
             ])
             .block(
@@ -95,6 +184,7 @@ pub fn render(state: &AppState, f: &mut Frame) {
                         .add_modifier(Modifier::BOLD),
                 )),
                 Line::from(format!("New Value: {}", state.input_value)),
+                global_line(state, "Ctrl+g"),
                 Line::from("Enter=confirm, Esc=cancel, ←/→ move cursor"),
             ])
             .block(
@@ -113,6 +203,153 @@ pub fn render(state: &AppState, f: &mut Frame) {
             let y = area.y + 2;
             f.set_cursor_position((x, y));
         }
+        Mode::ListEdit(key) => {
+            let key = key.clone();
+            let areas = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(if state.list_adding {
+                    [Constraint::Min(3), Constraint::Length(3)]
+                } else {
+                    [Constraint::Min(3), Constraint::Length(0)]
+                })
+                .split(chunks[0]);
+            state.list_scroll_offset = scroll_offset(
+                state.list_scroll_offset,
+                state.list_index,
+                state.list_items.len(),
+                areas[0].height.saturating_sub(2) as usize,
+            );
+            let items: Vec<ListItem> = state
+                .list_items
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    let marker = if i == state.list_index { "> " } else { "  " };
+                    let (exists, color) = if Path::new(entry).exists() {
+                        ("✓ ", Color::Green)
+                    } else {
+                        ("✗ ", Color::Red)
+                    };
+                    let style = if i == state.list_index {
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(marker, style),
+                        Span::styled(exists, Style::default().fg(color)),
+                        Span::styled(entry.clone(), style),
+                    ]))
+                })
+                .collect();
+            let list = List::new(items).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Blue))
+                    .title(format!("Editing: {}", key)),
+            );
+            let mut list_state = ListState::default()
+                .with_offset(state.list_scroll_offset)
+                .with_selected(Some(state.list_index));
+            f.render_stateful_widget(list, areas[0], &mut list_state);
+            if state.list_adding {
+                let input = Paragraph::new(format!("New entry: {}", state.input_value)).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(Color::Blue)),
+                );
+                f.render_widget(input, areas[1]);
+                // "New entry: " is 11 characters.
+                let x = areas[1].x + 1 + 11 + state.input_cursor_value as u16;
+                f.set_cursor_position((x, areas[1].y + 1));
+            }
+        }
+        Mode::Review => {
+            let review = Paragraph::new(diff_lines(state)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Blue))
+                    .title(format!("Pending changes ({})", state.staged.len())),
+            );
+            f.render_widget(review, chunks[0]);
+        }
+        Mode::ApplyFile => render_prompt(
+            state,
+            f,
+            chunks[0],
+            "Apply",
+            &format!("Write {} change(s) to dotenv file", state.staged.len()),
+            "File: ",
+        ),
+        Mode::SaveFile => render_prompt(
+            state,
+            f,
+            chunks[0],
+            "Save",
+            "Save environment with pending changes to dotenv file",
+            "File: ",
+        ),
+        Mode::Run => render_prompt(
+            state,
+            f,
+            chunks[0],
+            "Run",
+            "Run command with pending changes",
+            "Command: ",
+        ),
+        Mode::Bulk => {
+            let mut lines = vec![Line::from(Span::styled(
+                format!("{} variable(s) marked", state.marked_entries().len()),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ))];
+            lines.extend(
+                state
+                    .marked_entries()
+                    .into_iter()
+                    .map(|(k, _)| Line::from(k)),
+            );
+            let modal = Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Blue))
+                    .title("Bulk"),
+            );
+            f.render_widget(modal, centered_rect(60, 60, chunks[0]));
+        }
+        Mode::BulkExport => render_prompt(
+            state,
+            f,
+            chunks[0],
+            "Export",
+            "Export marked variables to dotenv file",
+            "File: ",
+        ),
+        Mode::BulkRename => render_prompt(
+            state,
+            f,
+            chunks[0],
+            "Rename",
+            "Add prefix to names of marked variables",
+            "Prefix: ",
+        ),
+        Mode::BulkConfirm(action) => {
+            let modal = Paragraph::new(confirm_lines(state, action)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::Blue))
+                    .title("Confirm"),
+            );
+            f.render_widget(modal, centered_rect(60, 60, chunks[0]));
+        }
         Mode::Delete(key) => {
             let modal = Paragraph::new(vec![
                 Line::from(Span::styled(
@@ -120,6 +357,7 @@ pub fn render(state: &AppState, f: &mut Frame) {
                     Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
                 )),
                 Line::from("Confirm deletion? [y]es / [n]o"),
+                global_line(state, "g"),
             ])
             .block(
                 Block::default()
@@ -135,10 +373,28 @@ pub fn render(state: &AppState, f: &mut Frame) {
     }
 
     // Footer: display instructions or a message.
-    let footer_text = if let Some(ref msg) = state.message {
+    let footer_text = if state.mode == Mode::Search {
+        format!("Search: {}", state.filter)
+    } else if let Some(ref msg) = state.message {
         msg.clone()
+    } else if state.mode == Mode::Review && state.file.is_some() {
+        "Press [s]ave to file, [x] discard all, Esc back".to_string()
+    } else if state.mode == Mode::Review {
+        "Apply to [p]rocess or [g]lobal environment or dotenv [f]ile, [x] discard all, Esc back"
+            .to_string()
+    } else if state.mode == Mode::Bulk {
+        "Press [d]elete, [e]xport to file, copy [c] names or [v] name=value lines, [r]ename with prefix, Esc back"
+            .to_string()
+    } else if let Mode::BulkConfirm(_) = state.mode {
+        "Press [y]es to confirm, [n]o to go back".to_string()
+    } else if let Mode::ListEdit(_) = state.mode {
+        "Press [a]dd, [d]elete, [K/J] or Shift+↑/↓ move entry, Enter save, Esc cancel".to_string()
+    } else if state.file.is_some() {
+        "Press [a]dd [e]dit [d]elete [K/J] move [Space] mark [b]ulk [/]search [n/N] match [f] jump [s]ort [v] reveal [u]ndo [Ctrl+r] redo [h]istory [w] review [!] run [Ctrl+l] reload [Ctrl+q] quit"
+            .to_string()
     } else {
-        "Press [a]dd, [e]dit, [d]elete, [Ctrl+r] reload, [Ctrl+q] quit".to_string()
+        "Press [a]dd [e]dit [d]elete [Space] mark [b]ulk [/]search [n/N] match [f] jump [s]ort [v] reveal [u]ndo [Ctrl+r] redo [h]istory [w] review [!] run [S]ave [Ctrl+l] reload [Ctrl+q] quit"
+            .to_string()
     };
     let footer = Paragraph::new(footer_text)
         .block(
@@ -149,6 +405,223 @@ pub fn render(state: &AppState, f: &mut Frame) {
         )
         .wrap(Wrap { trim: true });
     f.render_widget(footer, chunks[1]);
+    if state.mode == Mode::Search {
+        // "Search: " is 8 characters, inner area is offset by the border.
+        let x = chunks[1].x + 1 + 8 + state.filter.chars().count() as u16;
+        f.set_cursor_position((x, chunks[1].y + 1));
+    }
+}
+
+// Helper: checkbox choosing whether operation is applied globally, empty for dotenv file.
+fn global_line(state: &AppState, toggle: &str) -> Line<'static> {
+    if state.file.is_some() {
+        return Line::from("");
+    }
+    let (checkbox, color) = if state.input_global {
+        ("[x]", Color::Yellow)
+    } else {
+        ("[ ]", Color::White)
+    };
+    Line::from(vec![
+        Span::styled(checkbox, Style::default().fg(color)),
+        Span::raw(format!(" Apply globally right away ({} to toggle)", toggle)),
+    ])
+}
+
+// Helper: summarize which variables bulk operation affects.
+fn confirm_lines(state: &AppState, action: &BulkAction) -> Vec<Line<'static>> {
+    let entries = state.marked_entries();
+    let heading = match action {
+        BulkAction::Delete => format!("Delete {} variable(s)?", entries.len()),
+        BulkAction::Export(path) => format!("Export {} variable(s) to {}?", entries.len(), path),
+        BulkAction::CopyNames => format!("Copy names of {} variable(s)?", entries.len()),
+        BulkAction::CopyValues => format!("Copy {} variable(s) with values?", entries.len()),
+        BulkAction::Rename(_) => format!("Rename {} variable(s)?", entries.len()),
+    };
+    let mut lines = vec![Line::from(Span::styled(
+        heading,
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    for (key, _) in entries {
+        let line = match action {
+            BulkAction::Rename(prefix) => {
+                let renamed = format!("{}{}", prefix, key);
+                // Renaming to name of existing variable replaces its value
+                let replaces = if state.working_value(&renamed).is_some() {
+                    " (replaces existing)"
+                } else {
+                    ""
+                };
+                format!("{} -> {}{}", key, renamed, replaces)
+            }
+            _ => key,
+        };
+        lines.push(Line::from(line));
+    }
+    lines
+}
+
+// Helper: draw modal asking for single value typed into input_value.
+fn render_prompt(
+    state: &AppState,
+    f: &mut Frame,
+    area: Rect,
+    title: &str,
+    heading: &str,
+    label: &str,
+) {
+    let modal = Paragraph::new(vec![
+        Line::from(Span::styled(
+            heading.to_string(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("{}{}", label, state.input_value)),
+        Line::from("Enter=confirm, Esc=back, ←/→ move cursor"),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Blue))
+            .title(title.to_string()),
+    )
+    .wrap(Wrap { trim: true });
+    let area = centered_rect(60, 40, area);
+    f.render_widget(modal, area);
+    // Input is on inner line 1, after the label.
+    let x = area.x + 1 + label.len() as u16 + state.input_cursor_value as u16;
+    f.set_cursor_position((x, area.y + 2));
+}
+
+// Helper: format full value for the detail pane.
+//
+// Path lists get one entry per line marked with whether it exists,
+// JSON is pretty-printed and other values are shown as is.
+fn detail_lines(key: &str, value: &str) -> Vec<Line<'static>> {
+    if is_path_list(key) && !value.is_empty() {
+        return env::split_paths(value)
+            .map(|entry| {
+                let (marker, color) = if Path::new(&entry).exists() {
+                    ("✓ ", Color::Green)
+                } else {
+                    ("✗ ", Color::Red)
+                };
+                Line::from(vec![
+                    Span::styled(marker, Style::default().fg(color)),
+                    Span::raw(entry.display().to_string()),
+                ])
+            })
+            .collect();
+    }
+    let trimmed = value.trim_start();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && let Ok(json) = serde_json::from_str::<serde_json::Value>(value)
+        && let Ok(pretty) = serde_json::to_string_pretty(&json)
+    {
+        return pretty
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+    }
+    value
+        .lines()
+        .map(|line| Line::from(line.to_string()))
+        .collect()
+}
+
+// Helper: list changes made during the session, most recent first, undone ones dimmed.
+fn history_lines(state: &AppState) -> Vec<Line<'static>> {
+    let describe = |change: &PlannedChange| {
+        let mask = |value: &str| state.display_value(&change.key, value).into_owned();
+        match (&change.old, &change.new) {
+            (Some(old), Some(new)) => {
+                format!("change {}: {:?} -> {:?}", change.key, mask(old), mask(new))
+            }
+            (None, Some(new)) => format!("create {} = {:?}", change.key, mask(new)),
+            (Some(old), None) => format!("remove {} (was {:?})", change.key, mask(old)),
+            (None, None) => format!("remove {}", change.key),
+        }
+    };
+    let undone = state.redo_stack.iter().flatten().map(|change| {
+        Line::from(Span::styled(
+            format!("{} (undone)", describe(change)),
+            Style::default().fg(Color::DarkGray),
+        ))
+    });
+    let done = state
+        .undo_stack
+        .iter()
+        .rev()
+        .flat_map(|changes| changes.iter().rev())
+        .map(|change| Line::from(describe(change)));
+    let lines: Vec<Line> = undone.chain(done).collect();
+    if lines.is_empty() {
+        vec![Line::from("No changes yet")]
+    } else {
+        lines
+    }
+}
+
+// Helper: show pending changes as a diff, removed values in red and new ones in green.
+fn diff_lines(state: &AppState) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    for change in &state.staged {
+        let line = |sign: &str, value: &str, color: Color| {
+            Line::from(Span::styled(
+                format!(
+                    "{} {}={}",
+                    sign,
+                    change.key,
+                    state.display_value(&change.key, value)
+                ),
+                Style::default().fg(color),
+            ))
+        };
+        if let Some(old) = &change.old {
+            lines.push(line("-", old, Color::Red));
+        }
+        if let Some(new) = &change.new {
+            lines.push(line("+", new, Color::Green));
+        }
+    }
+    if state.order_changed {
+        lines.push(Line::from(Span::styled(
+            "~ variables reordered",
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    lines
+}
+
+// Helper: split text into spans, highlighting characters at given positions.
+fn highlight(text: &str, positions: &[usize], style: Style) -> Vec<Span<'static>> {
+    let matched = style.fg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+    let mut spans: Vec<Span> = vec![];
+    let mut current = String::new();
+    let mut current_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_matched = positions.contains(&i);
+        if is_matched != current_matched && !current.is_empty() {
+            let text = std::mem::take(&mut current);
+            spans.push(Span::styled(
+                text,
+                if current_matched { matched } else { style },
+            ));
+        }
+        current_matched = is_matched;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        spans.push(Span::styled(
+            current,
+            if current_matched { matched } else { style },
+        ));
+    }
+    spans
 }
 
 // Helper: create a centered rectangle using percentage dimensions.
@@ -187,17 +660,250 @@ mod tests {
     #[test]
     fn test_draw_list_mode() -> io::Result<()> {
         let backend = TestBackend::new(80, 30);
-        let state = AppState::new(vec![
+        let mut state = AppState::new(vec![
             ("VAR1".to_string(), "VALUE1".to_string()),
             ("VAR2".to_string(), "VALUE2".to_string()),
         ]);
         let mut terminal = Terminal::new(backend).unwrap();
         terminal.draw(|f| {
-            super::render(&state, f);
+            super::render(&mut state, f);
+        })?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_list_mode_masks_secrets() -> io::Result<()> {
+        let backend = TestBackend::new(80, 30);
+        let mut state = AppState::new(vec![("API_TOKEN".to_string(), "hidden".to_string())]);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let screen = |terminal: &Terminal<TestBackend>| {
+            terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+        terminal.draw(|f| {
+            super::render(&mut state, f);
+        })?;
+        assert!(!screen(&terminal).contains("hidden"));
+        assert!(screen(&terminal).contains("********"));
+        state.reveal_secrets = true;
+        terminal.draw(|f| {
+            super::render(&mut state, f);
+        })?;
+        assert!(screen(&terminal).contains("hidden"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_staged_changes() -> io::Result<()> {
+        let backend = TestBackend::new(80, 30);
+        let mut state = AppState::new(vec![
+            ("KEPT".to_string(), "1".to_string()),
+            ("GONE".to_string(), "2".to_string()),
+        ]);
+        state.stage("GONE", None);
+        state.stage("ADDED", Some("3".to_string()));
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| {
+            super::render(&mut state, f);
+        })?;
+        let buffer = terminal.backend().buffer().clone();
+        let row = |y: u16| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        };
+        assert!(row(0).contains("2 pending"));
+        assert!(row(2).contains("- GONE"));
+        assert!(buffer[(5, 2)].modifier.contains(Modifier::CROSSED_OUT));
+        assert!(row(3).contains("+ ADDED"));
+
+        state.mode = Mode::Review;
+        terminal.draw(|f| {
+            super::render(&mut state, f);
+        })?;
+        let buffer = terminal.backend().buffer().clone();
+        let screen: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Pending changes (2)"));
+        assert!(screen.contains("- GONE=2"));
+        assert!(screen.contains("+ ADDED=3"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_global_line() {
+        let mut state = AppState::new(vec![]);
+        assert_eq!(
+            global_line(&state, "g").to_string(),
+            "[ ] Apply globally right away (g to toggle)"
+        );
+        state.input_global = true;
+        assert!(global_line(&state, "g").to_string().starts_with("[x]"));
+        state.file = Some(".env".into());
+        assert_eq!(global_line(&state, "g").to_string(), "");
+    }
+
+    #[test]
+    fn test_confirm_lines() {
+        let mut state = AppState::new(vec![
+            ("HOST".to_string(), "a".to_string()),
+            ("APP_PORT".to_string(), "1".to_string()),
+            ("PORT".to_string(), "2".to_string()),
+        ]);
+        state.marked = vec!["PORT".to_string(), "HOST".to_string()];
+        let lines: Vec<String> = confirm_lines(&state, &BulkAction::Rename("APP_".to_string()))
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Rename 2 variable(s)?",
+                "HOST -> APP_HOST",
+                "PORT -> APP_PORT (replaces existing)",
+            ]
+        );
+        state.stage("HOST", None);
+        assert_eq!(
+            confirm_lines(&state, &BulkAction::Delete)[0].to_string(),
+            "Delete 1 variable(s)?"
+        );
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let mut state = AppState::new(vec![("API_TOKEN".to_string(), "old".to_string())]);
+        state.stage("API_TOKEN", Some("new".to_string()));
+        state.stage("B", Some("1".to_string()));
+        let lines: Vec<String> = diff_lines(&state)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec!["- API_TOKEN=********", "+ API_TOKEN=********", "+ B=1"]
+        );
+    }
+
+    #[test]
+    fn test_draw_search_mode() -> io::Result<()> {
+        let backend = TestBackend::new(80, 30);
+        let mut state = AppState::new(vec![
+            ("HOME".to_string(), "/root".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ]);
+        let mut terminal = Terminal::new(backend).unwrap();
+        state.mode = Mode::Search;
+        state.filter = "pth".to_string();
+        terminal.draw(|f| {
+            super::render(&mut state, f);
         })?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("PATH"));
+        assert!(!screen.contains("HOME"));
+        assert!(screen.contains("Search: pth"));
         Ok(())
     }
 
+    #[test]
+    fn test_draw_detail_pane() -> io::Result<()> {
+        let backend = TestBackend::new(100, 30);
+        let mut state = AppState::new(vec![("GREETING".to_string(), "first\nsecond".to_string())]);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| {
+            super::render(&mut state, f);
+        })?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        // Newline is replaced in the list and value is split in the detail pane
+        assert!(screen.contains("first↵second"));
+        assert!(screen.contains("│first "));
+        assert!(screen.contains("│second "));
+        Ok(())
+    }
+
+    #[test]
+    fn test_detail_lines() {
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines.iter().map(|line| line.to_string()).collect()
+        };
+        assert_eq!(
+            text(detail_lines("CONFIG", "{\"a\":[1]}")),
+            vec!["{", "  \"a\": [", "    1", "  ]", "}"]
+        );
+        assert_eq!(text(detail_lines("GREETING", "a\nb")), vec!["a", "b"]);
+        let dir = env::temp_dir();
+        let missing = dir.join("envfetch-missing-dir");
+        let path = env::join_paths([&dir, &missing]).unwrap();
+        assert_eq!(
+            text(detail_lines("PATH", &path.to_string_lossy())),
+            vec![
+                format!("✓ {}", dir.display()),
+                format!("✗ {}", missing.display())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_history_lines() {
+        let mut state = AppState::new(vec![]);
+        assert_eq!(history_lines(&state)[0].to_string(), "No changes yet");
+        state.undo_stack = vec![
+            vec![PlannedChange {
+                key: "A".to_string(),
+                old: None,
+                new: Some("1".to_string()),
+            }],
+            vec![PlannedChange {
+                key: "API_TOKEN".to_string(),
+                old: Some("old".to_string()),
+                new: Some("new".to_string()),
+            }],
+        ];
+        state.redo_stack = vec![vec![PlannedChange {
+            key: "B".to_string(),
+            old: Some("2".to_string()),
+            new: None,
+        }]];
+        let lines: Vec<String> = history_lines(&state)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "remove B (was \"2\") (undone)",
+                "change API_TOKEN: \"********\" -> \"********\"",
+                "create A = \"1\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let style = Style::default();
+        let spans = highlight("PATH", &[0, 2, 3], style);
+        let texts: Vec<&str> = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(texts, vec!["P", "A", "TH"]);
+        assert_ne!(spans[0].style, style);
+        assert_eq!(spans[1].style, style);
+    }
+
     #[test]
     fn test_draw_add_mode() -> io::Result<()> {
         let backend = TestBackend::new(80, 30);
@@ -208,7 +914,7 @@ mod tests {
         state.input_key = "NEW_VAR".to_string();
         state.input_value = "NEW_VALUE".to_string();
         terminal.draw(|f| {
-            super::render(&state, f);
+            super::render(&mut state, f);
         })?;
         Ok(())
     }
@@ -221,11 +927,36 @@ mod tests {
         state.mode = Mode::Edit("VAR1".to_string());
         state.input_value = "EDITED_VALUE".to_string();
         terminal.draw(|f| {
-            super::render(&state, f);
+            super::render(&mut state, f);
         })?;
         Ok(())
     }
 
+    #[test]
+    fn test_draw_list_edit_mode() -> io::Result<()> {
+        let backend = TestBackend::new(80, 30);
+        let mut state = AppState::new(vec![("PATH".to_string(), "/".to_string())]);
+        let mut terminal = Terminal::new(backend).unwrap();
+        state.mode = Mode::ListEdit("PATH".to_string());
+        state.list_items = vec!["/".to_string(), "/envfetch-missing-dir".to_string()];
+        state.list_adding = true;
+        state.input_value = "/new".to_string();
+        terminal.draw(|f| {
+            super::render(&mut state, f);
+        })?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("> ✓ /"));
+        assert!(screen.contains("✗ /envfetch-missing-dir"));
+        assert!(screen.contains("New entry: /new"));
+        Ok(())
+    }
+
     #[test]
     fn test_draw_delete_mode() -> io::Result<()> {
         let backend = TestBackend::new(80, 30);
@@ -233,7 +964,7 @@ mod tests {
         let mut terminal = Terminal::new(backend).unwrap();
         state.mode = Mode::Delete("VAR1".to_string());
         terminal.draw(|f| {
-            super::render(&state, f);
+            super::render(&mut state, f);
         })?;
         Ok(())
     }
@@ -245,7 +976,7 @@ mod tests {
         let mut terminal = Terminal::new(backend).unwrap();
         state.show_message("Test message", Duration::from_secs(2));
         terminal.draw(|f| {
-            super::render(&state, f);
+            super::render(&mut state, f);
         })?;
         Ok(())
     }
@@ -262,8 +993,45 @@ mod tests {
         state.current_index = 15;
         state.scroll_offset = 10;
         terminal.draw(|f| {
-            super::render(&state, f);
+            super::render(&mut state, f);
+        })?;
+        // All entries fit into the list, so nothing is scrolled out
+        assert_eq!(state.list_height, 25);
+        assert_eq!(state.scroll_offset, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_uses_scroll_offset() -> io::Result<()> {
+        // Footer takes 3 rows and borders 2, leaving 10 rows for the list
+        let backend = TestBackend::new(80, 15);
+        let mut state = AppState::new(
+            (0..30)
+                .map(|i| (format!("VAR{:02}", i), "value".to_string()))
+                .collect(),
+        );
+        let mut terminal = Terminal::new(backend).unwrap();
+        let first_row = |terminal: &Terminal<TestBackend>| {
+            let buffer = terminal.backend().buffer();
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, 1)].symbol())
+                .collect::<String>()
+        };
+        state.select_position(usize::MAX);
+        terminal.draw(|f| {
+            super::render(&mut state, f);
+        })?;
+        assert_eq!(state.scroll_offset, 20);
+        assert!(first_row(&terminal).contains("VAR20"));
+
+        // Page moves by rows the list was drawn with, scrolling only as far as needed
+        state.move_selection(-(state.page_size() as isize), false);
+        assert_eq!(state.current_index, 19);
+        terminal.draw(|f| {
+            super::render(&mut state, f);
         })?;
+        assert_eq!(state.scroll_offset, 19);
+        assert!(first_row(&terminal).contains("VAR19"));
         Ok(())
     }
 }
@@ -4,10 +4,20 @@
 //! run processes with specific variable value, or delete specific variable
 //! to run process without it
 
+mod audit;
 mod commands;
+mod completions;
 mod config;
+mod docs;
+mod dotenv;
+mod format;
+mod global;
+mod history;
+mod hook;
 mod interactive;
 mod models;
+mod render;
+mod secrets;
 mod utils;
 mod variables;
 
@@ -71,8 +81,15 @@ mod tests {
         assert_eq!(
             args.command,
             Commands::Get(GetArgs {
-                key: "PATH".to_string(),
-                no_similar_names: false
+                keys: vec!["PATH".to_string()],
+                no_similar_names: false,
+                raw: false,
+                quoted: false,
+                default: None,
+                format: None,
+                json: false,
+                ignore_case: false,
+                fuzzy: false,
             })
         );
     }
@@ -83,16 +100,84 @@ mod tests {
         assert_eq!(
             args.command,
             Commands::Get(GetArgs {
-                key: "PATH".to_string(),
-                no_similar_names: true
+                keys: vec!["PATH".to_string()],
+                no_similar_names: true,
+                raw: false,
+                quoted: false,
+                default: None,
+                format: None,
+                json: false,
+                ignore_case: false,
+                fuzzy: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_get_command_with_multiple_keys_and_options() {
+        let args = Cli::parse_from([
+            "envfetch",
+            "get",
+            "HOME",
+            "PATH",
+            "--raw",
+            "--default",
+            "none",
+        ]);
+        assert_eq!(
+            args.command,
+            Commands::Get(GetArgs {
+                keys: vec!["HOME".to_string(), "PATH".to_string()],
+                no_similar_names: false,
+                raw: true,
+                quoted: false,
+                default: Some("none".to_string()),
+                format: None,
+                json: false,
+                ignore_case: false,
+                fuzzy: false,
             })
         );
     }
 
+    #[test]
+    fn test_get_command_raw_conflicts_with_json() {
+        assert!(Cli::try_parse_from(["envfetch", "get", "PATH", "--raw", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_set_command_with_validation() {
+        let args = Cli::parse_from([
+            "envfetch",
+            "set",
+            "KEY",
+            "VALUE",
+            "--global",
+            "--validation",
+            "portable",
+        ]);
+        assert!(matches!(
+            args.command,
+            Commands::Set(SetArgs {
+                validation: Some(ValidationProfile::Portable),
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_print_command() {
         let args = Cli::parse_from(["envfetch", "print"]);
-        assert_eq!(args.command, Commands::Print(PrintArgs { format: None }));
+        assert_eq!(
+            args.command,
+            Commands::Print(PrintArgs {
+                format: None,
+                header: None,
+                footer: None,
+                separator: None,
+                reveal: false,
+            })
+        );
     }
 
     #[test]
@@ -101,11 +186,186 @@ mod tests {
         assert_eq!(
             args.command,
             Commands::Print(PrintArgs {
-                format: Some("{name}: \"{value}\"".to_owned())
+                format: Some("{name}: \"{value}\"".to_owned()),
+                header: None,
+                footer: None,
+                separator: None,
+                reveal: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_global_list_command() {
+        let args = Cli::parse_from(["envfetch", "global", "list"]);
+        assert_eq!(
+            args.command,
+            Commands::Global(GlobalArgs {
+                command: GlobalCommands::List
+            })
+        );
+    }
+
+    #[test]
+    fn test_global_get_command() {
+        let args = Cli::parse_from(["envfetch", "global", "get", "VAR"]);
+        assert_eq!(
+            args.command,
+            Commands::Global(GlobalArgs {
+                command: GlobalCommands::Get(GlobalKeyArgs {
+                    key: "VAR".to_string()
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn test_history_command() {
+        let args = Cli::parse_from(["envfetch", "history"]);
+        assert_eq!(args.command, Commands::History);
+    }
+
+    #[test]
+    fn test_undo_command() {
+        let args = Cli::parse_from(["envfetch", "undo"]);
+        assert_eq!(args.command, Commands::Undo(UndoArgs { count: 1 }));
+        let args = Cli::parse_from(["envfetch", "undo", "-n", "3"]);
+        assert_eq!(args.command, Commands::Undo(UndoArgs { count: 3 }));
+    }
+
+    #[test]
+    fn test_shell_command() {
+        let args = Cli::parse_from(["envfetch", "shell", "--set", "A=1", "-s", "B=2"]);
+        assert_eq!(
+            args.command,
+            Commands::Shell(ShellArgs {
+                file: None,
+                set: vec!["A=1".to_string(), "B=2".to_string()],
+                nested: false,
+                validation: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_completions_command() {
+        let args = Cli::parse_from(["envfetch", "completions", "powershell"]);
+        assert_eq!(
+            args.command,
+            Commands::Completions(CompletionsArgs {
+                shell: clap_complete::Shell::PowerShell
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_command() {
+        let args = Cli::parse_from([
+            "envfetch",
+            "render",
+            "app.conf.in",
+            "--file",
+            ".env",
+            "--escape",
+            "json",
+        ]);
+        assert_eq!(
+            args.command,
+            Commands::Render(RenderArgs {
+                template: "app.conf.in".to_string(),
+                file: Some(".env".to_string()),
+                escape: TemplateEscape::Json,
+            })
+        );
+    }
+
+    #[test]
+    fn test_audit_command() {
+        let args = Cli::parse_from(["envfetch", "audit", "--file", ".env", "--json"]);
+        assert_eq!(
+            args.command,
+            Commands::Audit(AuditArgs {
+                file: Some(".env".to_string()),
+                json: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_interactive_command() {
+        let args = Cli::parse_from(["envfetch", "interactive"]);
+        assert_eq!(
+            args.command,
+            Commands::Interactive(InteractiveArgs {
+                file: None,
+                export: None,
+                validation: None,
+            })
+        );
+        let args = Cli::parse_from(["envfetch", "interactive", "--export", "fish"]);
+        assert_eq!(
+            args.command,
+            Commands::Interactive(InteractiveArgs {
+                file: None,
+                export: Some(HookShell::Fish),
+                validation: None,
+            })
+        );
+        let args = Cli::parse_from(["envfetch", "interactive", "--file", ".env"]);
+        assert_eq!(
+            args.command,
+            Commands::Interactive(InteractiveArgs {
+                file: Some(".env".to_string()),
+                export: None,
+                validation: None,
+            })
+        );
+        assert!(
+            Cli::try_parse_from(["envfetch", "interactive", "-f", ".env", "--export", "bash"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_docs_command() {
+        let args = Cli::parse_from(["envfetch", "docs", "man", "--output", "man"]);
+        assert_eq!(
+            args.command,
+            Commands::Docs(DocsArgs {
+                format: DocsFormat::Man,
+                output: Some("man".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn test_complete_command() {
+        let args = Cli::parse_from(["envfetch", "__complete", "--", "get", ""]);
+        assert_eq!(
+            args.command,
+            Commands::Complete(CompleteArgs {
+                words: vec!["get".to_string(), "".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn test_hook_command() {
+        let args = Cli::parse_from(["envfetch", "hook", "zsh"]);
+        assert_eq!(
+            args.command,
+            Commands::Hook(HookArgs {
+                shell: HookShell::Zsh
             })
         );
     }
 
+    #[test]
+    fn test_allow_command() {
+        let args = Cli::parse_from(["envfetch", "allow"]);
+        assert_eq!(args.command, Commands::Allow(AllowArgs { file: None }));
+    }
+
     #[test]
     fn test_init_config() {
         let args = Cli::parse_from(["envfetch", "init-config"]);
@@ -119,6 +379,8 @@ mod tests {
             args.command,
             Commands::Set(SetArgs {
                 global: false,
+                dry_run: false,
+                validation: None,
                 key: "VAR".to_string(),
                 value: "VALUE".to_string(),
                 process: vec!["npm".to_string(), "run".to_string()]
@@ -133,6 +395,8 @@ mod tests {
             args.command,
             Commands::Set(SetArgs {
                 global: true,
+                dry_run: false,
+                validation: None,
                 key: "VAR".to_string(),
                 value: "VALUE".to_string(),
                 process: vec![]
@@ -149,6 +413,8 @@ mod tests {
             args.command,
             Commands::Set(SetArgs {
                 global: true,
+                dry_run: false,
+                validation: None,
                 key: "VAR".to_string(),
                 value: "VALUE".to_string(),
                 process: vec!["npm".to_string(), "run".to_string()]
@@ -156,13 +422,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_set_command_with_dry_run_flag() {
+        let args = Cli::parse_from(["envfetch", "set", "VAR", "VALUE", "--dry-run"]);
+        assert_eq!(
+            args.command,
+            Commands::Set(SetArgs {
+                global: false,
+                dry_run: true,
+                validation: None,
+                key: "VAR".to_string(),
+                value: "VALUE".to_string(),
+                process: vec![]
+            })
+        );
+    }
+
     #[test]
     fn test_add_command_simple() {
-        let args = Cli::parse_from(["envfetch", "add", "PATH", "./executable", "--", "npm", "run"]);
+        let args = Cli::parse_from([
+            "envfetch",
+            "add",
+            "PATH",
+            "./executable",
+            "--",
+            "npm",
+            "run",
+        ]);
         assert_eq!(
             args.command,
             Commands::Add(AddArgs {
                 global: false,
+                dry_run: false,
+                validation: None,
                 key: "PATH".to_string(),
                 value: "./executable".to_string(),
                 process: vec!["npm".to_string(), "run".to_string()]
@@ -177,6 +469,8 @@ mod tests {
             args.command,
             Commands::Add(AddArgs {
                 global: true,
+                dry_run: false,
+                validation: None,
                 key: "PATH".to_string(),
                 value: "./executable".to_string(),
                 process: vec![]
@@ -200,6 +494,8 @@ mod tests {
             args.command,
             Commands::Add(AddArgs {
                 global: true,
+                dry_run: false,
+                validation: None,
                 key: "PATH".to_string(),
                 value: "./executable".to_string(),
                 process: vec!["npm".to_string(), "run".to_string()]
@@ -215,6 +511,8 @@ mod tests {
             Commands::Delete(DeleteArgs {
                 key: "VAR".to_string(),
                 global: false,
+                dry_run: false,
+                validation: None,
                 process: vec!["npm".to_string(), "run".to_string()]
             })
         );
@@ -228,6 +526,8 @@ mod tests {
             Commands::Delete(DeleteArgs {
                 key: "VAR".to_string(),
                 global: true,
+                dry_run: false,
+                validation: None,
                 process: vec![]
             })
         );
@@ -241,6 +541,8 @@ mod tests {
             Commands::Delete(DeleteArgs {
                 key: "VAR".to_string(),
                 global: true,
+                dry_run: false,
+                validation: None,
                 process: vec!["npm".to_string(), "run".to_string()]
             })
         );
@@ -1,7 +1,7 @@
 use std::error::Error;
 use std::fmt::Display;
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
 
 #[derive(Parser)]
@@ -27,7 +27,7 @@ pub struct Cli {
 #[derive(Subcommand, Debug, PartialEq, Eq)]
 pub enum Commands {
     /// Open envfetch in interactive mode with TUI.
-    Interactive,
+    Interactive(InteractiveArgs),
     /// Print value of environment variable.
     Get(GetArgs),
     /// Set environment variable and optionally run given process.
@@ -40,39 +40,286 @@ pub enum Commands {
     Load(LoadArgs),
     /// Print all environment variables.
     Print(PrintArgs),
+    /// Inspect variables persisted by global operations.
+    Global(GlobalArgs),
+    /// Show journal of global modifications.
+    History,
+    /// Revert latest global modifications.
+    Undo(UndoArgs),
+    /// Start interactive shell with modified environment.
+    Shell(ShellArgs),
+    /// Print shell hook that loads nearest allowed .env file on directory change.
+    Hook(HookArgs),
+    /// Allow shell hook to load dotenv file with its current content.
+    Allow(AllowArgs),
+    /// Render template substituting ${VAR} and {{VAR}} placeholders with variables.
+    Render(RenderArgs),
+    /// Scan environment or dotenv file for credentials and unsafe settings.
+    Audit(AuditArgs),
+    /// Generate shell completion script.
+    Completions(CompletionsArgs),
+    /// Generate man pages or markdown reference of command line interface.
+    Docs(DocsArgs),
+    /// Print completion candidates for given words, used by completion scripts.
+    #[command(hide = true, name = "__complete")]
+    Complete(CompleteArgs),
+    /// Print statements that sync shell with nearest .env file, used by shell hook.
+    #[command(hide = true)]
+    HookEnv(HookArgs),
     /// Initialize config file.
     InitConfig,
 }
 
+/// Args for interactive command
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct InteractiveArgs {
+    /// Edit variables of dotenv file instead of environment, file is created on save
+    #[arg(long, short, conflicts_with = "export")]
+    pub file: Option<String>,
+    /// Print statements applying changes made to process or global environment and pending ones
+    /// in given shell on exit, for use with eval.
+    /// Interface is drawn on stderr so that the statements can be captured.
+    #[arg(long, value_name = "SHELL")]
+    pub export: Option<HookShell>,
+    /// Rules for validating names of added variables, lenient by default
+    #[arg(long, value_enum)]
+    pub validation: Option<ValidationProfile>,
+}
+
 /// Args for print command
 #[derive(Args, Debug, PartialEq, Eq)]
 pub struct PrintArgs {
     /// Set custom format, by default {name} = "{value}" is used.
+    /// Supports {name}, {value}, {index}, {len} and filters like {value|shell}, {value|json},
+    /// {value|truncate:40}, {name|lower} and {name|upper}, and conditionals like
+    /// {if secret}...{else}...{end} checking first, last, empty or secret, optionally with not.
     #[arg(long, short)]
     pub format: Option<String>,
+    /// Text printed before variables, may contain {len}
+    #[arg(long)]
+    pub header: Option<String>,
+    /// Text printed after variables, may contain {len}
+    #[arg(long)]
+    pub footer: Option<String>,
+    /// Text printed between variables, new line by default
+    #[arg(long)]
+    pub separator: Option<String>,
+    /// Show values of variables that look like secrets
+    #[arg(long)]
+    pub reveal: bool,
 }
 
-/// Args for get command
+/// Args for global command
 #[derive(Args, Debug, PartialEq, Eq)]
-pub struct GetArgs {
+pub struct GlobalArgs {
+    #[command(subcommand)]
+    pub command: GlobalCommands,
+}
+
+/// Commands for working with globally set variables
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum GlobalCommands {
+    /// List variables from global store with their source and status.
+    List,
+    /// Print globally set variable with its source and status.
+    Get(GlobalKeyArgs),
+    /// Remove variable from global store.
+    Remove(GlobalKeyArgs),
+}
+
+/// Args for global get and remove commands
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct GlobalKeyArgs {
     /// Environment variable name
     #[arg(required = true)]
     pub key: String,
+}
+
+/// Args for undo command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct UndoArgs {
+    /// Number of modifications to revert
+    #[arg(long, short = 'n', default_value = "1")]
+    pub count: usize,
+}
+
+/// Args for shell command
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct ShellArgs {
+    /// Relative or absolute path to dotenv file to load variables from
+    #[arg(long, short)]
+    pub file: Option<String>,
+    /// Set variable in shell, can be used multiple times
+    #[arg(long = "set", short, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+    /// Allow starting shell inside another envfetch shell
+    #[arg(long)]
+    pub nested: bool,
+    /// Rules for validating variable names, lenient by default
+    #[arg(long, value_enum)]
+    pub validation: Option<ValidationProfile>,
+}
+
+/// Args for render command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct RenderArgs {
+    /// Template file to render
+    #[arg(required = true)]
+    pub template: String,
+    /// Dotenv file with variables that take precedence over environment
+    #[arg(long, short)]
+    pub file: Option<String>,
+    /// Escape substituted values for target format
+    #[arg(long, short, value_enum, default_value = "none")]
+    pub escape: TemplateEscape,
+}
+
+/// Args for audit command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct AuditArgs {
+    /// Dotenv file to audit instead of environment
+    #[arg(long, short)]
+    pub file: Option<String>,
+    /// Print findings as JSON array
+    #[arg(long, short)]
+    pub json: bool,
+}
+
+/// Formats that substituted values can be escaped for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemplateEscape {
+    /// Insert values as is
+    None,
+    /// Escape for use inside double quotes in shell
+    Shell,
+    /// Escape for use inside JSON string
+    Json,
+    /// Escape XML special characters
+    Xml,
+}
+
+/// Args for completions command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for
+    #[arg(required = true)]
+    pub shell: clap_complete::Shell,
+}
+
+/// Args for docs command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct DocsArgs {
+    /// Format of documentation
+    #[arg(required = true)]
+    pub format: DocsFormat,
+    /// Directory for man pages or file for markdown reference, printed to stdout if not set
+    #[arg(long, short)]
+    pub output: Option<String>,
+}
+
+/// Documentation formats supported by docs command
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocsFormat {
+    /// Roff man pages for envfetch and each subcommand
+    Man,
+    /// Single markdown file with all commands
+    Markdown,
+}
+
+/// Rules used to validate variable names
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationProfile {
+    /// Letters, digits and underscores, not starting with digit
+    Posix,
+    /// Uppercase letters, digits and underscores, not starting with digit
+    Portable,
+    /// Anything Windows accepts, up to 32767 characters
+    Windows,
+    /// Anything without spaces
+    #[default]
+    Lenient,
+}
+
+/// Args for hidden __complete command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct CompleteArgs {
+    /// Words after program name, the last one is being completed
+    #[arg(last = true, allow_hyphen_values = true, num_args = 0..)]
+    pub words: Vec<String>,
+}
+
+/// Shells supported by hook command and interactive exports
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Args for hook command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct HookArgs {
+    /// Shell to print hook for
+    #[arg(required = true)]
+    pub shell: HookShell,
+}
+
+/// Args for allow command
+#[derive(Args, Debug, PartialEq, Eq)]
+pub struct AllowArgs {
+    /// Path to dotenv file, by default nearest .env file is used
+    pub file: Option<String>,
+}
+
+/// Args for get command
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct GetArgs {
+    /// Environment variable names
+    #[arg(required = true, value_name = "KEY")]
+    pub keys: Vec<String>,
     /// Disable showing similar variables' names if variable not found
     #[arg(long, short = 's', default_value = "false")]
     pub no_similar_names: bool,
+    /// Print values without quotes and escaping
+    #[arg(long, short, conflicts_with_all = ["quoted", "format", "json"])]
+    pub raw: bool,
+    /// Print values with quotes and escaping, even if raw output is enabled in config
+    #[arg(long, short, conflicts_with_all = ["format", "json"])]
+    pub quoted: bool,
+    /// Value to print for variables that aren't set instead of failing
+    #[arg(long, short)]
+    pub default: Option<String>,
+    /// Set custom format like in print command, e.g. {name}={value}
+    #[arg(long, short, conflicts_with = "json")]
+    pub format: Option<String>,
+    /// Print variables as JSON object
+    #[arg(long, short)]
+    pub json: bool,
+    /// Match variable names ignoring case
+    #[arg(long, short)]
+    pub ignore_case: bool,
+    /// Use most similar variable name if there's no exact match
+    #[arg(long, short = 'z')]
+    pub fuzzy: bool,
 }
 
 /// Args for load command
-#[derive(Args, Debug, PartialEq, Eq)]
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
 pub struct LoadArgs {
     /// Globally set variable
     #[arg(required = false, long, short)]
     pub global: bool,
-    /// Process to start, not required if --global flag is set
+    /// Show changes without applying them or starting process
+    #[arg(required = false, long)]
+    pub dry_run: bool,
+    /// Rules for validating variable names, lenient by default
+    #[arg(long, value_enum)]
+    pub validation: Option<ValidationProfile>,
+    /// Process to start, not required if --global or --dry-run flag is set
     #[arg(
         last = true,
-        required_unless_present = "global",
+        required_unless_present_any = ["global", "dry_run"],
         allow_hyphen_values = true,
         num_args = 1..
     )]
@@ -84,7 +331,7 @@ pub struct LoadArgs {
 }
 
 /// Args for set command
-#[derive(Args, Debug, PartialEq, Eq)]
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
 pub struct SetArgs {
     /// Environment variable name
     #[arg(required = true)]
@@ -95,7 +342,13 @@ pub struct SetArgs {
     /// Globally set variable
     #[arg(required = false, long, short)]
     pub global: bool,
-    /// Process to start, not required if --global flag is set
+    /// Show changes without applying them or starting process
+    #[arg(required = false, long)]
+    pub dry_run: bool,
+    /// Rules for validating variable names, lenient by default
+    #[arg(long, value_enum)]
+    pub validation: Option<ValidationProfile>,
+    /// Process to start, not required if --global or --dry-run flag is set
     // #[arg(
     //     last = true,
     //     required_unless_present = "global",
@@ -113,7 +366,7 @@ pub struct SetArgs {
     // )]
     #[arg(
         last = true,
-        required_unless_present = "global",
+        required_unless_present_any = ["global", "dry_run"],
         allow_hyphen_values = true,
         num_args = 1..
     )]
@@ -121,7 +374,7 @@ pub struct SetArgs {
 }
 
 /// Args for add command
-#[derive(Args, Debug, PartialEq, Eq)]
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
 pub struct AddArgs {
     /// Environment variable name
     #[arg(required = true)]
@@ -132,10 +385,16 @@ pub struct AddArgs {
     /// Globally set variable
     #[arg(required = false, long, short)]
     pub global: bool,
-    /// Process to start, not required if --global flag is set
+    /// Show changes without applying them or starting process
+    #[arg(required = false, long)]
+    pub dry_run: bool,
+    /// Rules for validating variable names, lenient by default
+    #[arg(long, value_enum)]
+    pub validation: Option<ValidationProfile>,
+    /// Process to start, not required if --global or --dry-run flag is set
     #[arg(
         last = true,
-        required_unless_present = "global",
+        required_unless_present_any = ["global", "dry_run"],
         allow_hyphen_values = true,
         num_args = 1..
     )]
@@ -143,7 +402,7 @@ pub struct AddArgs {
 }
 
 /// Args for delete command
-#[derive(Args, Debug, PartialEq, Eq)]
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
 pub struct DeleteArgs {
     /// Environment variable name
     #[arg(required = true)]
@@ -151,10 +410,16 @@ pub struct DeleteArgs {
     /// Globally set variable
     #[arg(required = false, long, short)]
     pub global: bool,
-    /// Process to start, not required if --global flag is set
+    /// Show changes without applying them or starting process
+    #[arg(required = false, long)]
+    pub dry_run: bool,
+    /// Rules for validating variable names, lenient by default
+    #[arg(long, value_enum)]
+    pub validation: Option<ValidationProfile>,
+    /// Process to start, not required if --global or --dry-run flag is set
     #[arg(
         last = true,
-        required_unless_present = "global",
+        required_unless_present_any = ["global", "dry_run"],
         allow_hyphen_values = true,
         num_args = 1..
     )]
@@ -170,6 +435,12 @@ pub enum ErrorKind {
     FileError(String),
     CannotFindVariable(String, bool),
     NameValidationError(String),
+    ValueValidationError(String),
+    CannotReadGlobalVariables(String),
+    HistoryError(String),
+    NestedShellError,
+    AmbiguousVariable(String, Vec<String>),
+    TemplateError(String),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -213,14 +484,57 @@ impl Display for ErrorKind {
             ErrorKind::FileError(err) => write!(f, "File error: {}", err),
             ErrorKind::CannotFindVariable(name, _) => write!(f, "Can't find variable: {}", name),
             ErrorKind::NameValidationError(err) => write!(f, "Name validation error: {}", err),
+            ErrorKind::ValueValidationError(err) => {
+                write!(f, "Value validation error: {}", err)
+            }
+            ErrorKind::CannotReadGlobalVariables(err) => {
+                write!(f, "Can't read globally set variables: {}", err)
+            }
+            ErrorKind::HistoryError(err) => write!(f, "History error: {}", err),
+            ErrorKind::NestedShellError => write!(
+                f,
+                "Already inside envfetch shell, exit it first or use --nested"
+            ),
+            ErrorKind::AmbiguousVariable(key, candidates) => write!(
+                f,
+                "Variable name {} is ambiguous, candidates: {}",
+                key,
+                candidates.join(", ")
+            ),
+            ErrorKind::TemplateError(err) => write!(f, "Template error: {}", err),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+/// Format of print command in config, either template of entry or table with layout
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[serde(untagged)]
+pub enum PrintFormat {
+    Entry(String),
+    Layout {
+        entry: Option<String>,
+        header: Option<String>,
+        footer: Option<String>,
+        separator: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
 pub struct Config {
     /// Format, used to print variables using print command
-    pub print_format: Option<String>,
+    pub print_format: Option<PrintFormat>,
+    /// Print values in get command without quotes and escaping by default
+    pub get_raw: Option<bool>,
+    /// Minimal similarity score from 0 to 1 of names suggested for missing variables
+    pub suggestion_threshold: Option<f64>,
+    /// Maximal number of names suggested for missing variables
+    pub suggestion_count: Option<usize>,
+    /// Rules for validating variable names, used when command doesn't set them
+    pub validation: Option<ValidationProfile>,
+    /// Name patterns of variables that are never masked as secrets, e.g. PUBLIC_*
+    pub secret_allow: Option<Vec<String>>,
+    /// Name patterns of variables that are always masked as secrets
+    pub secret_deny: Option<Vec<String>>,
 }
 
 #[cfg(test)]
@@ -255,6 +569,35 @@ mod tests {
                 ErrorKind::NameValidationError("Variable name cannot be empty".to_string()),
                 "Name validation error: Variable name cannot be empty",
             ),
+            (
+                ErrorKind::ValueValidationError(
+                    "Variable value cannot contain NUL byte".to_string(),
+                ),
+                "Value validation error: Variable value cannot contain NUL byte",
+            ),
+            (
+                ErrorKind::CannotReadGlobalVariables("unsupported shell".to_string()),
+                "Can't read globally set variables: unsupported shell",
+            ),
+            (
+                ErrorKind::HistoryError("Permission denied".to_string()),
+                "History error: Permission denied",
+            ),
+            (
+                ErrorKind::NestedShellError,
+                "Already inside envfetch shell, exit it first or use --nested",
+            ),
+            (
+                ErrorKind::AmbiguousVariable(
+                    "path".to_string(),
+                    vec!["PATH".to_string(), "Path".to_string()],
+                ),
+                "Variable name path is ambiguous, candidates: PATH, Path",
+            ),
+            (
+                ErrorKind::TemplateError("unterminated placeholder at line 1".to_string()),
+                "Template error: unterminated placeholder at line 1",
+            ),
         ];
 
         for (error, expected) in test_cases {
@@ -0,0 +1,243 @@
+use crate::models::{ErrorKind, TemplateEscape};
+
+/// Substitute `${VAR}` and `{{VAR}}` placeholders in template
+///
+/// Placeholders support `VAR:-default` for unset or empty variables and
+/// `VAR:?message` to require non-empty value, `$$` is rendered as `$`.
+/// Every unresolved placeholder is reported in one error.
+pub fn render_template<F>(
+    template: &str,
+    lookup: F,
+    escape: TemplateEscape,
+) -> Result<String, ErrorKind>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(template.len());
+    let mut unresolved = vec![];
+    let mut position = 0;
+    while position < template.len() {
+        let rest = &template[position..];
+        let (expression, length) = if rest.starts_with("$$") {
+            output.push('$');
+            position += 2;
+            continue;
+        } else if let Some(inner) = rest.strip_prefix("${") {
+            let end = inner
+                .find('}')
+                .ok_or_else(|| unterminated(template, position))?;
+            (&inner[..end], end + 3)
+        } else if let Some(inner) = rest.strip_prefix("{{") {
+            let end = inner
+                .find("}}")
+                .ok_or_else(|| unterminated(template, position))?;
+            (inner[..end].trim(), end + 4)
+        } else {
+            let character = rest.chars().next().unwrap_or_default();
+            output.push(character);
+            position += character.len_utf8();
+            continue;
+        };
+
+        let placeholder = Placeholder::parse(expression);
+        if placeholder.name.is_empty() {
+            return Err(ErrorKind::TemplateError(format!(
+                "empty placeholder at line {}",
+                line_number(template, position)
+            )));
+        }
+        match placeholder.resolve(&lookup) {
+            Ok(value) => output.push_str(&escape_value(&value, escape)),
+            Err(message) => unresolved.push(format!(
+                "{} at line {}{}",
+                placeholder.name,
+                line_number(template, position),
+                message
+                    .map(|message| format!(" ({})", message))
+                    .unwrap_or_default()
+            )),
+        }
+        position += length;
+    }
+
+    if !unresolved.is_empty() {
+        return Err(ErrorKind::TemplateError(format!(
+            "unresolved placeholders: {}",
+            unresolved.join(", ")
+        )));
+    }
+    Ok(output)
+}
+
+/// Parsed placeholder expression
+struct Placeholder<'a> {
+    name: &'a str,
+    default: Option<&'a str>,
+    required: Option<&'a str>,
+}
+
+impl<'a> Placeholder<'a> {
+    fn parse(expression: &'a str) -> Self {
+        if let Some((name, default)) = expression.split_once(":-") {
+            Placeholder {
+                name: name.trim(),
+                default: Some(default),
+                required: None,
+            }
+        } else if let Some((name, message)) = expression.split_once(":?") {
+            Placeholder {
+                name: name.trim(),
+                default: None,
+                required: Some(message.trim()),
+            }
+        } else {
+            Placeholder {
+                name: expression.trim(),
+                default: None,
+                required: None,
+            }
+        }
+    }
+
+    /// Get value of placeholder or optional message explaining why it's missing
+    fn resolve<F>(&self, lookup: F) -> Result<String, Option<String>>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let value = lookup(self.name);
+        if let Some(default) = self.default {
+            return Ok(value
+                .filter(|value| !value.is_empty())
+                .unwrap_or(default.to_string()));
+        }
+        if let Some(message) = self.required {
+            return value.filter(|value| !value.is_empty()).ok_or_else(|| {
+                Some(if message.is_empty() {
+                    "required".to_string()
+                } else {
+                    message.to_string()
+                })
+            });
+        }
+        value.ok_or(None)
+    }
+}
+
+/// Escape value for given target format
+fn escape_value(value: &str, escape: TemplateEscape) -> String {
+    match escape {
+        TemplateEscape::None => value.to_string(),
+        // Safe inside double quotes
+        TemplateEscape::Shell => value
+            .chars()
+            .flat_map(|c| match c {
+                '\\' | '"' | '$' | '`' => vec!['\\', c],
+                _ => vec![c],
+            })
+            .collect(),
+        // Contents of JSON string without surrounding quotes
+        TemplateEscape::Json => {
+            let quoted = serde_json::to_string(value).expect("Failed to serialize string");
+            quoted[1..quoted.len() - 1].to_string()
+        }
+        TemplateEscape::Xml => value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;"),
+    }
+}
+
+/// Error for placeholder without closing braces
+fn unterminated(template: &str, position: usize) -> ErrorKind {
+    ErrorKind::TemplateError(format!(
+        "unterminated placeholder at line {}",
+        line_number(template, position)
+    ))
+}
+
+/// Get line number of byte position in template
+fn line_number(template: &str, position: usize) -> usize {
+    template[..position].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("localhost".to_string()),
+            "PORT" => Some("8080".to_string()),
+            "EMPTY" => Some("".to_string()),
+            "SPECIAL" => Some("a \"b\" <c> & $d".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_render_both_syntaxes() {
+        let result = render_template(
+            "url=http://${HOST}:{{ PORT }}/\n",
+            lookup,
+            TemplateEscape::None,
+        )
+        .unwrap();
+        assert_eq!(result, "url=http://localhost:8080/\n");
+    }
+
+    #[test]
+    fn test_render_defaults_and_required() {
+        let result = render_template(
+            "${MISSING:-default} ${EMPTY:-fallback} {{HOST:?host is required}}",
+            lookup,
+            TemplateEscape::None,
+        )
+        .unwrap();
+        assert_eq!(result, "default fallback localhost");
+    }
+
+    #[test]
+    fn test_render_dollar_escape_and_unicode() {
+        let result = render_template("cost: $$5 — ${HOST}", lookup, TemplateEscape::None).unwrap();
+        assert_eq!(result, "cost: $5 — localhost");
+    }
+
+    #[test]
+    fn test_render_lists_all_unresolved() {
+        let result = render_template(
+            "${FIRST}\n{{ SECOND }}\n${EMPTY:?must be set}\n${EMPTY}",
+            lookup,
+            TemplateEscape::None,
+        );
+        assert!(matches!(
+            result,
+            Err(ErrorKind::TemplateError(message)) if message
+                == "unresolved placeholders: FIRST at line 1, SECOND at line 2, EMPTY at line 3 (must be set)"
+        ));
+    }
+
+    #[test]
+    fn test_render_unterminated() {
+        assert!(matches!(
+            render_template("a\n${HOST", lookup, TemplateEscape::None),
+            Err(ErrorKind::TemplateError(message)) if message == "unterminated placeholder at line 2"
+        ));
+        assert!(matches!(
+            render_template("{{}}", lookup, TemplateEscape::None),
+            Err(ErrorKind::TemplateError(message)) if message == "empty placeholder at line 1"
+        ));
+    }
+
+    #[test]
+    fn test_render_escaping() {
+        let render = |escape| render_template("${SPECIAL}", lookup, escape).unwrap();
+        assert_eq!(render(TemplateEscape::Shell), "a \\\"b\\\" <c> & \\$d");
+        assert_eq!(render(TemplateEscape::Json), "a \\\"b\\\" <c> & $d");
+        assert_eq!(
+            render(TemplateEscape::Xml),
+            "a &quot;b&quot; &lt;c&gt; &amp; $d"
+        );
+    }
+}
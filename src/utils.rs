@@ -1,18 +1,14 @@
+use std::env;
 #[cfg(test)]
 use std::process::Stdio;
 use std::process::{Command, ExitStatus};
 
-use crate::models::ErrorKind;
+use crate::models::{ErrorKind, ValidationProfile};
 use log::{error, info};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-/// Runs given command using system shell
-pub fn run(process: String) -> Result<ExitStatus, ErrorKind> {
-    if process.is_empty() {
-        error!("got 0 arguments as command name");
-        return Err(ErrorKind::StartingProcessError);
-    }
-
+/// Build command that runs given process using system shell
+pub fn shell_command(process: &str) -> Command {
     // Use platform-specific shell commands
     #[cfg(windows)]
     let (shell, shell_arg) = ("cmd", "/C");
@@ -21,6 +17,17 @@ pub fn run(process: String) -> Result<ExitStatus, ErrorKind> {
 
     let mut cmd = Command::new(shell);
     cmd.arg(shell_arg).arg(process);
+    cmd
+}
+
+/// Runs given command using system shell
+pub fn run(process: String) -> Result<ExitStatus, ErrorKind> {
+    if process.is_empty() {
+        error!("got 0 arguments as command name");
+        return Err(ErrorKind::StartingProcessError);
+    }
+
+    let mut cmd = shell_command(&process);
 
     #[cfg(test)]
     cmd.stderr(Stdio::null())
@@ -41,27 +48,168 @@ pub fn run(process: String) -> Result<ExitStatus, ErrorKind> {
     }
 }
 
-/// Validate variable name
-pub fn validate_var_name(name: &str) -> Result<(), String> {
+/// Get user's shell, falling back to system default one
+pub fn get_user_shell() -> String {
+    #[cfg(windows)]
+    let (variable, fallback) = ("COMSPEC", "cmd");
+    #[cfg(not(windows))]
+    let (variable, fallback) = ("SHELL", "sh");
+
+    env::var(variable)
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or(fallback.to_owned())
+}
+
+/// Maximal length of variable name on Windows
+const WINDOWS_MAX_NAME_LENGTH: usize = 32767;
+
+/// Validate variable name using rules of given profile
+pub fn validate_var_name(name: &str, profile: ValidationProfile) -> Result<(), String> {
     if name.is_empty() {
         return Err("Variable name cannot be empty".to_string());
     }
-    if name.contains(' ') {
-        return Err("Variable name cannot contain spaces".to_string());
+    // These are rejected by every platform and make env::set_var panic
+    if name.contains('\0') {
+        return Err("Variable name cannot contain NUL byte".to_string());
+    }
+    if name.contains('=') {
+        return Err("Variable name cannot contain '='".to_string());
+    }
+    match profile {
+        ValidationProfile::Lenient => {
+            if name.contains(' ') {
+                return Err("Variable name cannot contain spaces".to_string());
+            }
+        }
+        ValidationProfile::Windows => {
+            if name.chars().count() > WINDOWS_MAX_NAME_LENGTH {
+                return Err(format!(
+                    "Variable name cannot be longer than {} characters",
+                    WINDOWS_MAX_NAME_LENGTH
+                ));
+            }
+        }
+        ValidationProfile::Posix | ValidationProfile::Portable => {
+            if name.starts_with(|c: char| c.is_ascii_digit()) {
+                return Err("Variable name cannot start with a digit".to_string());
+            }
+            let portable = profile == ValidationProfile::Portable;
+            for c in name.chars() {
+                if c.is_ascii_lowercase() && portable {
+                    return Err(format!(
+                        "Variable name cannot contain lowercase letter '{}' in portable profile",
+                        c
+                    ));
+                }
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(format!(
+                        "Variable name cannot contain {:?}, only {}letters, digits and underscores are allowed",
+                        c,
+                        if portable { "uppercase " } else { "" }
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate variable value
+pub fn validate_var_value(value: &str) -> Result<(), String> {
+    if value.contains('\0') {
+        return Err("Variable value cannot contain NUL byte".to_string());
     }
     Ok(())
 }
 
-/// Returns vector of string that are similar by threshold to given string in given vector
-pub fn find_similar_string(string: String, strings: Vec<String>, threshold: f64) -> Vec<String> {
-    strings
+/// Minimal similarity score of suggested names by default
+pub const DEFAULT_SUGGESTION_THRESHOLD: f64 = 0.6;
+
+/// Maximal number of suggested names by default
+pub const DEFAULT_SUGGESTION_COUNT: usize = 5;
+
+/// Returns strings similar by threshold to given string, most similar first and at most limit
+pub fn find_similar_string(
+    string: String,
+    strings: Vec<String>,
+    threshold: f64,
+    limit: usize,
+) -> Vec<String> {
+    rank_similar_strings(&string, &strings, threshold)
+        .into_iter()
+        .take(limit)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// Returns strings similar by threshold to given string with their scores, most similar first
+pub fn rank_similar_strings(
+    string: &str,
+    strings: &[String],
+    threshold: f64,
+) -> Vec<(f64, String)> {
+    let mut scored = strings
         .par_iter()
-        .filter(|name| {
-            similar_string::compare_similarity(string.to_lowercase(), name.to_lowercase())
-                > threshold
-        })
-        .map(|name| name.to_string())
-        .collect::<Vec<_>>()
+        .map(|name| (similarity_score(string, name), name.to_string()))
+        .filter(|(score, _)| *score > threshold)
+        .collect::<Vec<_>>();
+    scored.sort_by(|(a_score, a_name), (b_score, b_name)| {
+        b_score.total_cmp(a_score).then_with(|| a_name.cmp(b_name))
+    });
+    scored
+}
+
+/// Score case-insensitive similarity of two strings from 0 to 1
+pub fn similarity_score(string: &str, name: &str) -> f64 {
+    let (string, name) = (string.to_lowercase(), name.to_lowercase());
+    if string == name {
+        return 1.0;
+    }
+    let length = string.chars().count().max(name.chars().count()) as f64;
+    let shorter = string.chars().count().min(name.chars().count()) as f64;
+    let ratio = similar_string::compare_similarity(string.clone(), name.clone());
+    let distance = 1.0 - edit_distance(&string, &name) as f64 / length;
+    // Prefer names that contain the whole string, e.g. PATH for PAT, weighted by covered part
+    let containment = if name.starts_with(&string) || string.starts_with(&name) {
+        0.5 + 0.5 * shorter / length
+    } else if name.contains(&string) || string.contains(&name) {
+        0.4 + 0.5 * shorter / length
+    } else {
+        0.0
+    };
+    ratio.max(distance).max(containment)
+}
+
+/// Count insertions, deletions, substitutions and transpositions to turn one string into another
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Check whether variable holds list of paths, e.g. PATH, PYTHONPATH or XDG_DATA_DIRS
+pub fn is_path_list(name: &str) -> bool {
+    let name = name.to_uppercase();
+    name.ends_with("PATH") || name.ends_with("DIRS")
 }
 
 #[cfg(test)]
@@ -80,7 +228,7 @@ mod tests {
         ];
 
         for name in valid_names {
-            assert!(validate_var_name(name).is_ok());
+            assert!(validate_var_name(name, ValidationProfile::Lenient).is_ok());
         }
     }
 
@@ -95,7 +243,7 @@ mod tests {
         ];
 
         for name in invalid_names {
-            let result = validate_var_name(name);
+            let result = validate_var_name(name, ValidationProfile::Lenient);
             assert!(result.is_err());
             assert_eq!(result.unwrap_err(), "Variable name cannot contain spaces");
         }
@@ -103,7 +251,7 @@ mod tests {
 
     #[test]
     fn test_validate_var_name_empty() {
-        let result = validate_var_name("");
+        let result = validate_var_name("", ValidationProfile::Lenient);
         assert!(
             result.is_err(),
             "Empty string should be invalid as per current implementation"
@@ -111,24 +259,89 @@ mod tests {
         assert_eq!(result.unwrap_err(), "Variable name cannot be empty");
     }
 
+    #[test]
+    fn test_validate_var_name_rejected_everywhere() {
+        for profile in [
+            ValidationProfile::Posix,
+            ValidationProfile::Portable,
+            ValidationProfile::Windows,
+            ValidationProfile::Lenient,
+        ] {
+            assert_eq!(
+                validate_var_name("A=B", profile).unwrap_err(),
+                "Variable name cannot contain '='"
+            );
+            assert_eq!(
+                validate_var_name("A\0B", profile).unwrap_err(),
+                "Variable name cannot contain NUL byte"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_var_name_posix() {
+        for name in ["PATH", "my_var", "_X1"] {
+            assert!(validate_var_name(name, ValidationProfile::Posix).is_ok());
+        }
+        assert_eq!(
+            validate_var_name("1ABC", ValidationProfile::Posix).unwrap_err(),
+            "Variable name cannot start with a digit"
+        );
+        assert_eq!(
+            validate_var_name("MY-VAR", ValidationProfile::Posix).unwrap_err(),
+            "Variable name cannot contain '-', only letters, digits and underscores are allowed"
+        );
+    }
+
+    #[test]
+    fn test_validate_var_name_portable() {
+        assert!(validate_var_name("MY_VAR_2", ValidationProfile::Portable).is_ok());
+        assert_eq!(
+            validate_var_name("My_VAR", ValidationProfile::Portable).unwrap_err(),
+            "Variable name cannot contain lowercase letter 'y' in portable profile"
+        );
+        assert_eq!(
+            validate_var_name("MY.VAR", ValidationProfile::Portable).unwrap_err(),
+            "Variable name cannot contain '.', only uppercase letters, digits and underscores are allowed"
+        );
+    }
+
+    #[test]
+    fn test_validate_var_name_windows() {
+        assert!(validate_var_name("Program Files(x86)", ValidationProfile::Windows).is_ok());
+        assert_eq!(
+            validate_var_name(&"A".repeat(32768), ValidationProfile::Windows).unwrap_err(),
+            "Variable name cannot be longer than 32767 characters"
+        );
+    }
+
+    #[test]
+    fn test_validate_var_value() {
+        assert!(validate_var_value("any value = ok").is_ok());
+        assert_eq!(
+            validate_var_value("a\0b").unwrap_err(),
+            "Variable value cannot contain NUL byte"
+        );
+    }
+
     #[test]
     fn test_find_similar_string_exact_match() {
         let strings = vec!["PATH".to_string(), "HOME".to_string(), "USER".to_string()];
-        let result = find_similar_string("PATH".to_string(), strings, 0.8);
+        let result = find_similar_string("PATH".to_string(), strings, 0.8, 5);
         assert_eq!(result, vec!["PATH"]);
     }
 
     #[test]
     fn test_find_similar_string_case_insensitive() {
         let strings = vec!["PATH".to_string(), "HOME".to_string(), "USER".to_string()];
-        let result = find_similar_string("path".to_string(), strings, 0.8);
+        let result = find_similar_string("path".to_string(), strings, 0.8, 5);
         assert_eq!(result, vec!["PATH"]);
     }
 
     #[test]
     fn test_find_similar_string_no_match() {
         let strings = vec!["PATH".to_string(), "HOME".to_string(), "USER".to_string()];
-        let result = find_similar_string("XXXXXX".to_string(), strings, 0.8);
+        let result = find_similar_string("XXXXXX".to_string(), strings, 0.8, 5);
         assert!(result.is_empty());
     }
 
@@ -140,13 +353,58 @@ mod tests {
             "TEXT".to_string(),
             "NONE".to_string(),
         ];
-        let result = find_similar_string("TEST".to_string(), strings, 0.5);
+        let result = find_similar_string("TEST".to_string(), strings, 0.5, 5);
         assert!(result.contains(&"TEST".to_string()));
         assert!(result.contains(&"TEXT".to_string()));
         assert!(result.contains(&"TSET".to_string()));
         assert!(!result.contains(&"NONE".to_string()));
     }
 
+    #[test]
+    fn test_find_similar_string_sorted_by_score() {
+        let strings = vec![
+            "TEXT".to_string(),
+            "NONE".to_string(),
+            "TEST_VALUE".to_string(),
+            "TSET".to_string(),
+        ];
+        let result = find_similar_string("TEST".to_string(), strings, 0.5, 5);
+        assert_eq!(result, vec!["TEXT", "TSET", "TEST_VALUE"]);
+    }
+
+    #[test]
+    fn test_find_similar_string_limit() {
+        let strings = vec![
+            "PATH_A".to_string(),
+            "PATH_B".to_string(),
+            "PATH_C".to_string(),
+        ];
+        let result = find_similar_string("PATH".to_string(), strings, 0.5, 2);
+        assert_eq!(result, vec!["PATH_A", "PATH_B"]);
+    }
+
+    #[test]
+    fn test_similarity_score_prefix() {
+        assert!(similarity_score("PAT", "PATH") > DEFAULT_SUGGESTION_THRESHOLD);
+        assert!(similarity_score("CARGO", "CARGO_HOME") > DEFAULT_SUGGESTION_THRESHOLD);
+        assert!(similarity_score("A", "ANDROID_HOME") < DEFAULT_SUGGESTION_THRESHOLD);
+        assert_eq!(similarity_score("path", "PATH"), 1.0);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("TEST", "TEST"), 0);
+        assert_eq!(edit_distance("TEST", "TSET"), 1);
+        assert_eq!(edit_distance("TEST", "TEXT"), 1);
+        assert_eq!(edit_distance("", "ABC"), 3);
+        assert_eq!(edit_distance("KITTEN", "SITTING"), 3);
+    }
+
+    #[test]
+    fn test_get_user_shell() {
+        assert!(!get_user_shell().is_empty());
+    }
+
     #[test]
     fn test_run_successful_command() {
         #[cfg(windows)]
@@ -238,4 +496,13 @@ mod tests {
         let result = run(cmd.to_string());
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_is_path_list() {
+        assert!(is_path_list("PATH"));
+        assert!(is_path_list("Path"));
+        assert!(is_path_list("LD_LIBRARY_PATH"));
+        assert!(is_path_list("XDG_DATA_DIRS"));
+        assert!(!is_path_list("HOME"));
+    }
 }
@@ -1,40 +1,99 @@
-use std::{env, io::Write};
+use std::{env, fmt::Display, io::Write, path::Path};
 
+use crate::format::{self, Layout};
+use crate::global;
+use crate::history::{self, Change};
 use crate::models::ErrorKind;
+use crate::secrets::SecretFilter;
 
 /// List of variables
 type VariablesList = Vec<(String, String)>;
 
-/// Print all environment variables
-pub fn print_env<W: Write>(format: &str, mut buffer: W) {
-    for (key, value) in get_variables() {
-        let entry = format.replace("{name}", &key).replace("{value}", &value);
-        writeln!(buffer, "{}", entry).expect("Failed to write to buffer");
+/// Modification of variable that command is going to make
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedChange {
+    pub key: String,
+    /// Current value, if variable is set
+    pub old: Option<String>,
+    /// New value, missing if variable is going to be deleted
+    pub new: Option<String>,
+}
+
+impl Display for PlannedChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.old, &self.new) {
+            (None, Some(new)) => write!(f, "create {} = {:?}", self.key, new),
+            (Some(old), Some(new)) if old == new => {
+                write!(f, "keep {} = {:?} (unchanged)", self.key, new)
+            }
+            (Some(old), Some(new)) => write!(f, "change {}: {:?} -> {:?}", self.key, old, new),
+            (Some(old), None) => write!(f, "remove {} (was {:?})", self.key, old),
+            (None, None) => write!(f, "remove {} (not set)", self.key),
+        }
     }
 }
 
+/// Print all environment variables, masking secrets if filter is given
+pub fn print_env<W: Write>(
+    layout: &Layout,
+    secrets: Option<&SecretFilter>,
+    buffer: W,
+) -> Result<(), ErrorKind> {
+    format::write_variables(&get_variables(), layout, secrets, buffer)
+}
+
 /// Get list of environment variables with values
 pub fn get_variables() -> VariablesList {
     env::vars().collect()
 }
 
-/// Set variable with given key and value
-pub fn set_variable(key: &str, value: &str, global: bool) -> Result<(), ErrorKind> {
+/// Get current value of variable, from global store if global is set
+pub fn current_value(key: &str, global: bool) -> Result<Option<String>, ErrorKind> {
+    if !global {
+        return Ok(env::var(key).ok());
+    }
+    match global::get_variable(&global::get_store()?, key) {
+        Ok(variable) => Ok(Some(variable.value)),
+        Err(ErrorKind::CannotFindVariable(_, _)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Apply planned change, recording global change in journal at given path
+pub fn apply_change(change: &PlannedChange, global: bool, journal: &Path) -> Result<(), ErrorKind> {
+    match &change.new {
+        Some(value) => set_variable(&change.key, value, global, journal),
+        None => delete_variable(change.key.clone(), global, journal),
+    }
+}
+
+/// Set variable with given key and value, recording global change in journal at given path
+pub fn set_variable(key: &str, value: &str, global: bool, journal: &Path) -> Result<(), ErrorKind> {
     if global {
+        let previous = current_value(key, true)
+            .map_err(|err| ErrorKind::CannotSetVariableGlobally(err.to_string()))?;
         if let Err(err) = globalenv::set_var(key, value) {
             return Err(ErrorKind::CannotSetVariableGlobally(err.to_string()));
         }
+        history::record_change(journal, key, previous, Some(value))?;
     } else {
         unsafe { env::set_var(key, value) };
     }
     Ok(())
 }
 
-/// Delete variable with given name
-pub fn delete_variable(name: String, global: bool) -> Result<(), ErrorKind> {
+/// Delete variable with given name, recording global change in journal at given path
+pub fn delete_variable(name: String, global: bool, journal: &Path) -> Result<(), ErrorKind> {
     if global {
-        if let Err(err) = globalenv::unset_var(&name) {
-            return Err(ErrorKind::CannotDeleteVariableGlobally(err.to_string()));
+        let previous = current_value(&name, true)
+            .map_err(|err| ErrorKind::CannotDeleteVariableGlobally(err.to_string()))?;
+        let store = global::get_store()
+            .map_err(|err| ErrorKind::CannotDeleteVariableGlobally(err.to_string()))?;
+        global::remove_variable(&store, &name)?;
+        unsafe { env::remove_var(&name) };
+        // Nothing to revert if variable wasn't persisted
+        if previous.is_some() {
+            history::record_change(journal, &name, previous, None)?;
         }
     } else {
         unsafe { env::remove_var(&name) };
@@ -42,11 +101,31 @@ pub fn delete_variable(name: String, global: bool) -> Result<(), ErrorKind> {
     Ok(())
 }
 
+/// Restore global state of variable recorded in given change
+pub fn revert_change(change: &Change) -> Result<(), ErrorKind> {
+    let store = global::get_store()?;
+    match &change.previous {
+        Some(previous) => {
+            // Startup files may already contain line with previous value
+            #[cfg(unix)]
+            global::remove_variable(&store, &change.key)?;
+            globalenv::set_var(&change.key, previous)
+                .map_err(|err| ErrorKind::CannotSetVariableGlobally(err.to_string()))
+        }
+        None => global::remove_variable(&store, &change.key),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
 
+    /// Get temporary journal, so tests never record changes in user's journal
+    fn temp_journal() -> tempfile::TempPath {
+        tempfile::NamedTempFile::new().unwrap().into_temp_path()
+    }
+
     #[test]
     fn test_get_variables_list() {
         unsafe { env::set_var("TEST_GET_VARIABLES", "test_value") };
@@ -55,9 +134,46 @@ mod tests {
         unsafe { env::remove_var("TEST_GET_VARIABLES") };
     }
 
+    #[test]
+    fn test_planned_change_display() {
+        let change = |old: Option<&str>, new: Option<&str>| PlannedChange {
+            key: "VAR".to_string(),
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        };
+        assert_eq!(change(None, Some("1")).to_string(), "create VAR = \"1\"");
+        assert_eq!(
+            change(Some("1"), Some("2")).to_string(),
+            "change VAR: \"1\" -> \"2\""
+        );
+        assert_eq!(
+            change(Some("1"), Some("1")).to_string(),
+            "keep VAR = \"1\" (unchanged)"
+        );
+        assert_eq!(
+            change(Some("1"), None).to_string(),
+            "remove VAR (was \"1\")"
+        );
+        assert_eq!(change(None, None).to_string(), "remove VAR (not set)");
+    }
+
+    #[test]
+    fn test_apply_change() {
+        let mut change = PlannedChange {
+            key: "TEST_APPLY_CHANGE".to_string(),
+            old: None,
+            new: Some("value".to_string()),
+        };
+        apply_change(&change, false, &temp_journal()).unwrap();
+        assert_eq!(env::var("TEST_APPLY_CHANGE").unwrap(), "value");
+        change.new = None;
+        apply_change(&change, false, &temp_journal()).unwrap();
+        assert!(env::var("TEST_APPLY_CHANGE").is_err());
+    }
+
     #[test]
     fn test_set_variable_simple() {
-        let result = set_variable("TEST_VAR", "test_value", false);
+        let result = set_variable("TEST_VAR", "test_value", false, &temp_journal());
         assert!(result.is_ok());
         assert_eq!(env::var("TEST_VAR").unwrap(), "test_value");
         unsafe { env::remove_var("TEST_VAR") };
@@ -67,7 +183,7 @@ mod tests {
     fn test_print_env() {
         unsafe { env::set_var("TEST_PRINT_VAR", "test_value") };
         let mut buffer = vec![];
-        print_env("{name} = \"{value}\"", &mut buffer);
+        print_env(&Layout::new("{name} = \"{value}\""), None, &mut buffer).unwrap();
         assert!(
             String::from_utf8(buffer)
                 .unwrap()
@@ -76,17 +192,29 @@ mod tests {
         unsafe { env::remove_var("TEST_PRINT_VAR") };
     }
 
+    #[test]
+    fn test_print_env_masks_secrets() {
+        unsafe { env::set_var("TEST_PRINT_MASK_TOKEN", "visible") };
+        let mut buffer = vec![];
+        let secrets = SecretFilter::default();
+        print_env(&Layout::new("{name}={value}"), Some(&secrets), &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains("TEST_PRINT_MASK_TOKEN=********"));
+        assert!(!output.contains("visible"));
+        unsafe { env::remove_var("TEST_PRINT_MASK_TOKEN") };
+    }
+
     #[test]
     fn test_delete_variable() {
         unsafe { env::set_var("TEST_DELETE_VAR", "test_value") };
-        let result = delete_variable("TEST_DELETE_VAR".to_string(), false);
+        let result = delete_variable("TEST_DELETE_VAR".to_string(), false, &temp_journal());
         assert!(result.is_ok());
         assert!(env::var("TEST_DELETE_VAR").is_err());
     }
 
     #[test]
     fn test_set_variable_empty_value() {
-        let result = set_variable("TEST_EMPTY_VAR", "", false);
+        let result = set_variable("TEST_EMPTY_VAR", "", false, &temp_journal());
         assert!(result.is_ok());
         assert_eq!(env::var("TEST_EMPTY_VAR").unwrap(), "");
         unsafe { env::remove_var("TEST_EMPTY_VAR") };
@@ -99,7 +227,7 @@ mod tests {
         unsafe { env::set_var("TEST_VAR_2", "value2") };
 
         let mut buffer = vec![];
-        print_env("{name} = \"{value}\"", &mut buffer);
+        print_env(&Layout::new("{name} = \"{value}\""), None, &mut buffer).unwrap();
         assert!(
             String::from_utf8(buffer.clone())
                 .unwrap()
@@ -121,7 +249,7 @@ mod tests {
         unsafe { env::set_var("TEST_EMPTY", "") };
 
         let mut buffer = vec![];
-        print_env("{name} = \"{value}\"", &mut buffer);
+        print_env(&Layout::new("{name} = \"{value}\""), None, &mut buffer).unwrap();
         assert!(
             String::from_utf8(buffer)
                 .unwrap()
@@ -136,7 +264,7 @@ mod tests {
         unsafe { env::set_var("TEST_SPECIAL", "value with spaces and $#@!") };
 
         let mut buffer = vec![];
-        print_env("{name} = \"{value}\"", &mut buffer);
+        print_env(&Layout::new("{name} = \"{value}\""), None, &mut buffer).unwrap();
         assert!(
             String::from_utf8(buffer)
                 .unwrap()
@@ -148,11 +276,16 @@ mod tests {
 
     #[test]
     fn test_set_variable_global() {
-        let result = set_variable("TEST_GLOBAL_VAR", "test_value", true);
+        let journal = temp_journal();
+        let result = set_variable("TEST_GLOBAL_VAR", "test_value", true, &journal);
         match result {
             Ok(_) => {
                 assert_eq!(env::var("TEST_GLOBAL_VAR").unwrap(), "test_value");
-                delete_variable("TEST_GLOBAL_VAR".to_string(), true).unwrap();
+                delete_variable("TEST_GLOBAL_VAR".to_string(), true, &journal).unwrap();
+                let changes = history::get_changes(&journal).unwrap();
+                assert_eq!(changes.len(), 2);
+                assert_eq!(changes[0].value.as_deref(), Some("test_value"));
+                assert_eq!(changes[1].value, None);
             }
             Err(ErrorKind::CannotSetVariableGlobally(_)) => {
                 // Test passes if we get permission error on non-admin run
@@ -163,12 +296,13 @@ mod tests {
 
     #[test]
     fn test_delete_variable_global() {
+        let journal = temp_journal();
         // First try to set a global variable
-        let set_result = set_variable("TEST_GLOBAL_DELETE", "test_value", true);
+        let set_result = set_variable("TEST_GLOBAL_DELETE", "test_value", true, &journal);
 
         // Only test deletion if we could set the variable (i.e., we have admin rights)
         if set_result.is_ok() {
-            let result = delete_variable("TEST_GLOBAL_DELETE".to_string(), true);
+            let result = delete_variable("TEST_GLOBAL_DELETE".to_string(), true, &journal);
             assert!(result.is_ok());
             assert!(env::var("TEST_GLOBAL_DELETE").is_err());
         }
@@ -176,9 +310,11 @@ mod tests {
 
     #[test]
     fn test_delete_nonexistent_variable_global() {
-        let result = delete_variable("NONEXISTENT_GLOBAL_VAR".to_string(), true);
+        let journal = temp_journal();
+        let result = delete_variable("NONEXISTENT_GLOBAL_VAR".to_string(), true, &journal);
         match result {
-            Ok(_) => {}
+            // Deleting variable that wasn't persisted leaves nothing to undo
+            Ok(_) => assert!(history::get_changes(&journal).unwrap().is_empty()),
             Err(ErrorKind::CannotDeleteVariableGlobally(_)) => {
                 // Test passes if we get permission error on non-admin run
             }
@@ -79,6 +79,31 @@ fn get_variable_doesnt_exists_similar_enabled() -> Result<(), Box<dyn std::error
     Ok(())
 }
 
+#[test]
+/// Test for delete and add commands with name that isn't set, both suggest similar names after plan
+fn delete_and_add_suggest_similar_names_on_stderr() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("DELETE_SUGGESTION_TEST", "1")
+        .args(["delete", "DELETE_SUGESTION_TEST", "--dry-run", "--", "true"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Did you mean").not())
+        .stderr(predicate::str::contains(
+            "Did you mean:\n  DELETE_SUGGESTION_TEST",
+        ));
+
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("DELETE_SUGGESTION_TEST", "1")
+        .args(["add", "DELETE_SUGESTION_TEST", "value", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Did you mean").not())
+        .stderr(predicate::str::contains(
+            "Did you mean:\n  DELETE_SUGGESTION_TEST",
+        ));
+    Ok(())
+}
+
 #[test]
 /// Test for get command if specified variable doesn't exist and showing similar variables is disabled
 fn get_variable_doesnt_exists_similar_disabled() -> Result<(), Box<dyn std::error::Error>> {
@@ -325,3 +350,400 @@ fn print_with_custom_format() -> Result<(), Box<dyn std::error::Error>> {
         .stdout(predicate::str::contains("FORMAT_TEST=Hello"));
     Ok(())
 }
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// Test for global list command, reads variables from shell startup file
+fn global_list_shows_source_and_status() -> Result<(), Box<dyn std::error::Error>> {
+    let home = assert_fs::TempDir::new()?;
+    home.child(".bashrc")
+        .write_str("alias ll='ls -l'\nexport GLOBAL_LIST_TEST=persisted\n")?;
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("HOME", home.path())
+        .env("SHELL", "/bin/bash")
+        .env_remove("GLOBAL_LIST_TEST")
+        .args(["global", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("GLOBAL_LIST_TEST = \"persisted\""))
+        .stdout(predicate::str::contains(".bashrc:2"))
+        .stdout(predicate::str::contains("[not loaded in current session]"));
+    Ok(())
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// Test for global remove command, removes variable from shell startup file
+fn global_remove_variable() -> Result<(), Box<dyn std::error::Error>> {
+    let home = assert_fs::TempDir::new()?;
+    let bashrc = home.child(".bashrc");
+    bashrc.write_str(
+        "export GLOBAL_REMOVE_TEST=1\nexport GLOBAL_REMOVE_TEST_DIR=/tmp\nalias show='echo $GLOBAL_REMOVE_TEST'\n",
+    )?;
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env("SHELL", "/bin/bash")
+        .args(["global", "remove", "GLOBAL_REMOVE_TEST"])
+        .assert()
+        .success();
+    bashrc.assert("export GLOBAL_REMOVE_TEST_DIR=/tmp\nalias show='echo $GLOBAL_REMOVE_TEST'\n");
+
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("HOME", home.path())
+        .env("SHELL", "/bin/bash")
+        .args(["global", "get", "GLOBAL_REMOVE_TEST"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Can't find variable: GLOBAL_REMOVE_TEST",
+        ));
+    Ok(())
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// Test for history and undo commands after global set
+fn undo_global_set() -> Result<(), Box<dyn std::error::Error>> {
+    let home = assert_fs::TempDir::new()?;
+    let bashrc = home.child(".bashrc");
+    bashrc.write_str("export UNDO_TEST=old\n")?;
+    let envfetch = |args: &[&str]| -> Result<Command, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("envfetch")?;
+        cmd.env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("SHELL", "/bin/bash")
+            .args(args);
+        Ok(cmd)
+    };
+
+    envfetch(&["set", "UNDO_TEST", "new", "--global"])?
+        .assert()
+        .success();
+    bashrc.assert(predicate::str::contains("export UNDO_TEST=new"));
+
+    envfetch(&["history"])?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("UNDO_TEST: \"old\" -> \"new\""));
+
+    envfetch(&["undo"])?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Reverted UNDO_TEST to \"old\""));
+    bashrc.assert(predicate::str::contains("export UNDO_TEST=old"));
+    bashrc.assert(predicate::str::contains("export UNDO_TEST=new").not());
+
+    envfetch(&["history"])?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("UNDO_TEST").not());
+    Ok(())
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// Test for history after failed global set, nothing should be recorded
+fn failed_global_set_not_recorded() -> Result<(), Box<dyn std::error::Error>> {
+    // Missing startup file makes global set fail
+    let home = assert_fs::TempDir::new()?;
+    let envfetch = |args: &[&str]| -> Result<Command, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("envfetch")?;
+        cmd.env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("SHELL", "/bin/bash")
+            .args(args);
+        Ok(cmd)
+    };
+
+    envfetch(&["set", "FAILED_SET_TEST", "value", "--global"])?
+        .assert()
+        .failure();
+    envfetch(&["history"])?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("FAILED_SET_TEST").not());
+    Ok(())
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// Test for load command with --dry-run and --global flags, nothing should be changed
+fn load_global_dry_run() -> Result<(), Box<dyn std::error::Error>> {
+    let home = assert_fs::TempDir::new()?;
+    let bashrc = home.child(".bashrc");
+    bashrc.write_str("export DRY_RUN_EXISTING=old\n")?;
+    let file = home.child(".env");
+    file.write_str("DRY_RUN_EXISTING=new\nDRY_RUN_NEW=value")?;
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("HOME", home.path())
+        .env("SHELL", "/bin/bash")
+        .env_remove("DRY_RUN_EXISTING")
+        .env_remove("DRY_RUN_NEW")
+        .arg("load")
+        .arg("--file")
+        .arg(file.path())
+        .args(["--global", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".bashrc"))
+        .stdout(predicate::str::contains(
+            "create DRY_RUN_EXISTING = \"new\"\n    currently persisted as \"old\"",
+        ))
+        .stdout(predicate::str::contains("create DRY_RUN_NEW = \"value\""));
+    bashrc.assert("export DRY_RUN_EXISTING=old\n");
+    Ok(())
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// Test for bash hook, dotenv file is loaded only after it's allowed and unloaded on leaving
+fn hook_loads_allowed_dotenv() -> Result<(), Box<dyn std::error::Error>> {
+    let home = assert_fs::TempDir::new()?;
+    let project = home.child("project");
+    project.child("nested").create_dir_all()?;
+    project
+        .child(".env")
+        .write_str("HOOK_TEST_VAR='from dotenv'")?;
+    let envfetch = Command::cargo_bin("envfetch")?
+        .get_program()
+        .to_string_lossy()
+        .to_string();
+    let script = format!(
+        r#"eval "$('{envfetch}' hook bash)"
+cd project/nested && _envfetch_hook && echo "before allow: $HOOK_TEST_VAR"
+'{envfetch}' allow >/dev/null && _envfetch_hook && echo "after allow: $HOOK_TEST_VAR"
+cd ../.. && _envfetch_hook && echo "after leaving: ${{HOOK_TEST_VAR-unset}}""#
+    );
+    let mut cmd = Command::new("bash");
+    cmd.current_dir(home.path())
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env_remove("HOOK_TEST_VAR")
+        .args(["-c", &script])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("before allow: \n"))
+        .stdout(predicate::str::contains("after allow: from dotenv\n"))
+        .stdout(predicate::str::contains("after leaving: unset\n"))
+        .stderr(predicate::str::contains("is blocked"));
+    Ok(())
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+/// Test for shell command, started shell should see modified variables and marker
+fn shell_command_applies_variables() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::cargo_bin("envfetch")?;
+    cmd.env("SHELL", "/bin/sh")
+        .env_remove("ENVFETCH_SHELL")
+        .args(["shell", "--set", "SHELL_TEST_VAR=hello"])
+        .write_stdin("echo \"$ENVFETCH_SHELL $SHELL_TEST_VAR\"\nexit 3\n")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("1 hello"));
+    Ok(())
+}
+
+#[test]
+/// Test for shell command, nesting should be refused without --nested flag
+fn shell_command_refuses_nesting() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_SHELL", "1")
+        .arg("shell")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Already inside envfetch shell"));
+    Ok(())
+}
+
+#[test]
+fn completions_bash_script() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("complete -F _envfetch_dynamic"));
+    Ok(())
+}
+
+#[test]
+fn complete_variable_names() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_COMPLETE_TEST", "1")
+        .args(["__complete", "--", "get", "ENVFETCH_COMPLETE_T"])
+        .assert()
+        .success()
+        .stdout("ENVFETCH_COMPLETE_TEST\n");
+    Ok(())
+}
+
+#[test]
+fn docs_markdown_reference() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.args(["docs", "markdown"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## `envfetch get`"));
+    Ok(())
+}
+
+#[test]
+fn get_raw_value_with_default() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_GET_RAW", "C:\\foo")
+        .env_remove("ENVFETCH_GET_MISSING")
+        .args([
+            "get",
+            "ENVFETCH_GET_RAW",
+            "ENVFETCH_GET_MISSING",
+            "--raw",
+            "--default",
+            "none",
+        ])
+        .assert()
+        .success()
+        .stdout("C:\\foo\nnone\n");
+    Ok(())
+}
+
+#[test]
+fn get_ignore_case_reports_chosen_key() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_MIXED_CASE", "value")
+        .args(["get", "envfetch_mixed_case", "--ignore-case", "--raw"])
+        .assert()
+        .success()
+        .stdout("value\n")
+        .stderr(predicate::str::contains(
+            "using ENVFETCH_MIXED_CASE for envfetch_mixed_case",
+        ));
+
+    // Notice goes through logger, so it can be silenced
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_MIXED_CASE", "value")
+        .env("ENVFETCH_LOG", "error")
+        .args(["get", "envfetch_mixed_case", "--ignore-case", "--raw"])
+        .assert()
+        .success()
+        .stdout("value\n")
+        .stderr("");
+    Ok(())
+}
+
+#[test]
+fn render_template_with_dotenv_file() -> Result<(), Box<dyn std::error::Error>> {
+    let template = assert_fs::NamedTempFile::new("app.conf.in")?;
+    template
+        .write_str("host = \"${ENVFETCH_RENDER_HOST}\"\nport = {{ ENVFETCH_RENDER_PORT:-80 }}\n")?;
+    let dotenv = assert_fs::NamedTempFile::new(".env")?;
+    dotenv.write_str("ENVFETCH_RENDER_HOST=from-file\n")?;
+
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_RENDER_HOST", "from-env")
+        .arg("render")
+        .arg(template.path())
+        .arg("--file")
+        .arg(dotenv.path())
+        .assert()
+        .success()
+        .stdout("host = \"from-file\"\nport = 80\n");
+    Ok(())
+}
+
+#[test]
+fn render_template_reports_unresolved() -> Result<(), Box<dyn std::error::Error>> {
+    let template = assert_fs::NamedTempFile::new("app.conf.in")?;
+    template.write_str("${ENVFETCH_RENDER_MISSING_A}\n${ENVFETCH_RENDER_MISSING_B}\n")?;
+
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.arg("render")
+        .arg(template.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "ENVFETCH_RENDER_MISSING_A at line 1, ENVFETCH_RENDER_MISSING_B at line 2",
+        ));
+    Ok(())
+}
+
+#[test]
+fn print_with_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_PRINT_FILTER", "it's {name}")
+        .args(["print", "--format", "{name|lower}={value|shell}"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "envfetch_print_filter='it'\\''s {name}'",
+        ));
+    Ok(())
+}
+
+#[test]
+fn print_masks_secrets_unless_revealed() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_PRINT_TOKEN", "hunter2")
+        .args(["print", "--format", "{name}={value}"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ENVFETCH_PRINT_TOKEN=********"))
+        .stdout(predicate::str::contains("hunter2").not());
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.env("ENVFETCH_PRINT_TOKEN", "hunter2")
+        .args(["print", "--reveal", "--format", "{name}={value}"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ENVFETCH_PRINT_TOKEN=hunter2"));
+    Ok(())
+}
+
+#[test]
+fn audit_dotenv_file_reports_findings() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new(".env")?;
+    file.write_str("API_TOKEN=abc\nLD_PRELOAD=hook.so\n")?;
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.args(["audit", "--json", "--file"])
+        .arg(file.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "{\"severity\":\"high\",\"check\":\"credential\",\"subject\":\"API_TOKEN\"",
+        ))
+        .stdout(predicate::str::contains("\"check\":\"dangerous-variable\""));
+    file.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn audit_clean_dotenv_file() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new(".env")?;
+    file.write_str("GREETING=hello\n")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o600))?;
+    }
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.args(["audit", "--file"])
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout("No issues found\n");
+    file.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn interactive_rejects_invalid_dotenv_file() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new(".env")?;
+    file.write_str("TEST_VAR test_value\n")?;
+    let mut cmd = Command::cargo_bin("envfetch")?;
+    cmd.args(["interactive", "--file"])
+        .arg(file.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Parsing error"));
+    file.close().unwrap();
+    Ok(())
+}